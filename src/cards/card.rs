@@ -1,5 +1,8 @@
-use std::fmt::Display;
-use crate::cards::suit::Suit;
+use std::{fmt::Display, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::cards::{suit::Suit, theme::format_card};
 
 #[derive(Debug, Copy, Clone)]
 /**
@@ -28,28 +31,136 @@ impl Card {
             val, face, suit,
         }
     }
+
+    /**
+     * Create a card from its rank (1-13) and suit, giving aces and faces
+     * their `CardFace`
+     */
+    pub fn from_rank(val: u32, suit: Suit) -> Card {
+        let face = match val {
+            1  => Some(CardFace::A),
+            11 => Some(CardFace::J),
+            12 => Some(CardFace::Q),
+            13 => Some(CardFace::K),
+            _  => None,
+        };
+
+        Card::new(val, face, suit)
+    }
+
+    /**
+     * Returns whether this card counts as 10: a ten or a face card
+     */
+    pub fn is_ten_value(&self) -> bool {
+        self.val >= 10
+    }
+
+    /**
+     * Returns whether this card has the same rank and suit as `other`
+     */
+    pub fn same_as(&self, other: &Card) -> bool {
+        self.val == other.val && self.suit == other.suit
+    }
+
+    /**
+     * Get the card as a rank then a suit letter, as it is parsed.
+     * ex: `AS`, `10H`, `KC`
+     */
+    pub fn code(&self) -> String {
+        let rank = match self.val {
+            1  => String::from("A"),
+            11 => String::from("J"),
+            12 => String::from("Q"),
+            13 => String::from("K"),
+            v  => v.to_string(),
+        };
+        let suit = match self.suit {
+            Suit::Spades   => 'S',
+            Suit::Hearts   => 'H',
+            Suit::Diamonds => 'D',
+            Suit::Clubs    => 'C',
+        };
+        format!("{}{}", rank, suit)
+    }
 }
 
-// make a card printable
+// make a card printable in the current theme
 impl Display for Card {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // each suit has a unique color. color codes can be found
-        // here: https://gist.github.com/fnky/458719343aabd01cfb17a3a4f7296797
-        let color = match &self.suit {
-            Suit::Spades => 51,     // Cyan
-            Suit::Hearts => 206,    // Mid pink
-            Suit::Diamonds => 171,  // Magenta
-            Suit::Clubs => 159,     // Light blue
+        write!(f, "{}", format_card(self))
+    }
+}
+
+/**
+ * Reasons a card could not be parsed
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub enum CardParseError {
+    Empty,
+    InvalidRank(String),
+    InvalidSuit(String),
+}
+
+impl FromStr for Card {
+    type Err = CardParseError;
+
+    /**
+     * Parse a card as a rank then a suit letter, ex: `AS`, `10h`, `TD`, `kc`.
+     * Also accepts the printed form of a suit symbol then a rank, ex: `@A`
+     */
+    fn from_str(s: &str) -> Result<Card, CardParseError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(CardParseError::Empty);
+        }
+
+        // split off the suit from whichever end it is on
+        let first = s.chars().next().unwrap().len_utf8();
+        let last = s.len() - s.chars().last().unwrap().len_utf8();
+        let (rank, suit) = match "@#$%".contains(&s[..first]) {
+            true  => (&s[first..], &s[..first]),
+            false => (&s[..last], &s[last..]),
+        };
+        let suit: Suit = suit.parse()?;
+
+        // ranks by number, or letter for aces, tens, and faces
+        let val = match rank.to_ascii_uppercase().as_str() {
+            "A"       => 1,
+            "T"       => 10,
+            "J"       => 11,
+            "Q"       => 12,
+            "K"       => 13,
+            n => match n.parse::<u32>() {
+                Ok(v) if (2..=10).contains(&v) => v,
+                _ => return Err(CardParseError::InvalidRank(String::from(rank))),
+            },
         };
 
-        // Show a card as its suit symbol and its either 1) value for number 
-        // cards or 2) face for face cards.
-        // Each card is also colored based on suit, see above.
-        write!(f, "{}", 
-            match &self.face {
-                Some(face) => format!("[38;5;{}m{}{:?}[0m", color, self.suit, face),
-                None => format!("[38;5;{}m{}{}[0m", color, self.suit, self.val),
-            }
-        )
+        Ok(Card::from_rank(val, suit))
+    }
+}
+
+// sent as its code so messages stay readable, ex: "10H"
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(de::Error::custom)
+    }
+}
+
+// describe a card parse error for the player
+impl Display for CardParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CardParseError::Empty => write!(f, "no card given"),
+            CardParseError::InvalidRank(rank) => write!(f, "invalid rank {:?}, expected A, 2-10, T, J, Q, or K", rank),
+            CardParseError::InvalidSuit(suit) => write!(f, "invalid suit {:?}, expected S, H, D, or C", suit),
+        }
     }
 }
@@ -1,19 +1,45 @@
-use super::{card::{Card, CardFace}, hand::Hand, suit::Suit};
-use rand::prelude::SliceRandom;
+use std::fmt::Display;
+
+use super::{card::Card, hand::Hand, scenario::ScenarioError, shuffle::ShuffleStyle, suit::Suit};
+use crate::game::variant::Variant;
+use rand::{prelude::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
 /**
  * A Deck has a draw pile and discard pile.
- * Also stores the number of 52 card decks are in the final Deck
+ * Also stores the number of decks in the final Deck, the game mode the
+ * decks were built for, how discards are returned to play, and how the
+ * cards are shuffled
  */
+#[derive(Clone)]
 pub struct Deck {
     size: usize,
+    variant: Variant,
+    shoe: ShoeMode,
+    shuffle: ShuffleStyle,
+    cut_at: usize,      // cards left behind the cut card. 0 if there is none
     pub draw_pile: Pile,
     pub discard_pile: Pile,
 }
 
+/**
+ * How discarded cards make their way back into the draw pile
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShoeMode {
+    // discards wait until the shoe runs out, then everything is shuffled
+    #[default]
+    Shoe,
+    // a continuous shuffler puts discards back at random after every round.
+    // the `buffer` most recent discards are held in the machine until
+    // pushed through by later rounds
+    Continuous { buffer: usize },
+}
+
 /**
  * Standard pile of cards
  */
+#[derive(Clone)]
 pub struct Pile {
     cards: Vec<Card>,
 }
@@ -29,9 +55,10 @@ impl Pile {
     }
 
     /**
-     * Create a pile of cards with cards for `decks` decks of cards
+     * Create a pile of cards with cards for `decks` decks of cards.
+     * Variants without tens build 48 card decks
      */
-    pub fn new_full(decks: usize) -> Pile {
+    pub fn new_full(decks: usize, variant: Variant) -> Pile {
         // start with an empty pile
         let mut p = Pile::new_empty(decks);
 
@@ -44,16 +71,12 @@ impl Pile {
             // val is 1-14, so %13+1
             let val = ((i % 13) + 1) as u32;
 
-            // get the display face of the card
-            let face = match val {
-                01 => Some(CardFace::A),
-                11 => Some(CardFace::J),
-                12 => Some(CardFace::Q),
-                13 => Some(CardFace::K),
-                __ => None,
-            };
+            p.cards.push(Card::from_rank(val, suit));
+        }
 
-            p.cards.push(Card::new(val, face, suit));
+        // take out the ten number cards. faces stay
+        if variant.removes_tens() {
+            p.cards.retain(|c| !(c.face.is_none() && c.val == 10));
         }
 
         p
@@ -85,45 +108,130 @@ impl Pile {
 
 impl Deck {
     /**
-     * Create a new single `Deck` with the cards of `decks` decks.
+     * Create a new single `Deck` with the cards of `decks` decks, built for
+     * the `variant` game mode, returning discards by `shoe`, and shuffled by
+     * `shuffle`.
      * Comes with both a draw pile and discard pile
      */
-    pub fn new(decks: usize) -> Deck {
+    pub fn new(decks: usize, variant: Variant, shoe: ShoeMode, shuffle: ShuffleStyle) -> Deck {
         // start with a full draw pile and empty discard pile
         let mut deck = Deck {
             size: decks,
-            draw_pile: Pile::new_full(decks),
+            variant,
+            shoe,
+            shuffle,
+            cut_at: 0,
+            draw_pile: Pile::new_full(decks, variant),
             discard_pile: Pile::new_empty(decks),
         };
 
-        // shuffle the draw pile then return
+        // new cards are always washed to a perfect shuffle, then the cut
+        // card is placed
         deck.draw_pile.shuffle();
+        deck.place_cut_card();
         deck
     }
 
+    /**
+     * Stack the shoe so `cards` are drawn first, in order.
+     * Each stacked card is taken out of the rest of the shoe, and the
+     * remaining cards are shuffled beneath, so the shoe keeps its size.
+     * Returns an error, leaving every card shuffled, if a card is not in the
+     * shoe as many times as it is stacked
+     */
+    pub fn stack(&mut self, cards: &[Card]) -> Result<(), ScenarioError> {
+        // start from every card, shuffled
+        self.reshuffle();
+
+        // take out the stacked cards
+        let mut rest = self.draw_pile.cards.clone();
+        for card in cards {
+            match rest.iter().position(|c| c.same_as(card)) {
+                Some(pos) => {
+                    rest.remove(pos);
+                },
+                None => return Err(ScenarioError::NotInShoe {
+                    card: card.code(),
+                    wanted: cards.iter().filter(|c| c.same_as(card)).count(),
+                    in_shoe: self.draw_pile.cards.iter().filter(|c| c.same_as(card)).count(),
+                }),
+            }
+        }
+
+        // cards are drawn from the end, so place the first card last
+        rest.extend(cards.iter().rev());
+        self.draw_pile.cards = rest;
+        Ok(())
+    }
+
     /**
      * Given a `Hand`, take all cards from the `Hand` and place them on top of
      * the discard pile
      */
     pub fn discard_hand(&mut self, hand: &mut Hand) {
         // while cards are in hand, take the card and place in discard
-        loop {
-            match hand.take_card() {
-                Some(c) => self.discard_pile.place(c),
-                None    => break,
-            }
+        while let Some(c) = hand.take_card() {
+            self.discard_pile.place(c);
+        }
+    }
+
+    /**
+     * Finish a round of play.
+     * With a continuous shuffler, discards past the machine's buffer are
+     * placed back into the draw pile at random positions, oldest first
+     */
+    pub fn end_round(&mut self) {
+        match self.shoe {
+            ShoeMode::Continuous { buffer } => {
+                let mut rng = rand::rng();
+                let returning = self.discard_pile.cards.len().saturating_sub(buffer);
+                for card in self.discard_pile.cards.drain(..returning) {
+                    let pos = rng.random_range(0..=self.draw_pile.cards.len());
+                    self.draw_pile.cards.insert(pos, card);
+                }
+            },
+            // shuffle once the cut card comes out
+            ShoeMode::Shoe => {
+                if self.cut_at > 0 && self.draw_pile.cards.len() <= self.cut_at {
+                    self.reshuffle();
+                }
+            },
         }
     }
 
     /**
      * Take all cards in the discard pile, place them on top of the draw pile,
      * then shuffle all cards.
+     * A dealer shuffle with a plug instead places the undealt cards into the
+     * discards as one block.
      * Empties the discard pile
      */
     pub fn reshuffle(&mut self) {
-        self.draw_pile.cards.extend(self.discard_pile.cards.clone());
-        self.discard_pile.cards.clear();
-        self.draw_pile.shuffle();
+        match self.shuffle {
+            ShuffleStyle::Human(human) if human.plug => {
+                let plug = std::mem::take(&mut self.draw_pile.cards);
+                let pos = rand::rng().random_range(0..=self.discard_pile.cards.len());
+                self.discard_pile.cards.splice(pos..pos, plug);
+                self.draw_pile.cards = std::mem::take(&mut self.discard_pile.cards);
+            },
+            _ => {
+                self.draw_pile.cards.extend(self.discard_pile.cards.clone());
+                self.discard_pile.cards.clear();
+            },
+        }
+
+        self.shuffle.shuffle(&mut self.draw_pile.cards);
+        self.place_cut_card();
+    }
+
+    /**
+     * Place the cut card in the draw pile, for shuffles that use one
+     */
+    fn place_cut_card(&mut self) {
+        self.cut_at = match self.shuffle {
+            ShuffleStyle::Human(human) => human.place_cut_card(self.draw_pile.cards.len()),
+            ShuffleStyle::Perfect => 0,
+        };
     }
 
     /**
@@ -132,4 +240,174 @@ impl Deck {
     pub fn size(&self) -> usize {
         self.size
     }
+
+    /**
+     * Returns the number of cards left to draw before the discards come back
+     */
+    pub fn remaining(&self) -> usize {
+        self.draw_pile.cards.len()
+    }
+
+    /**
+     * Returns the game mode this `Deck` was built for
+     */
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /**
+     * Returns how this `Deck` returns discards to play
+     */
+    pub fn shoe(&self) -> ShoeMode {
+        self.shoe
+    }
+
+    /**
+     * Returns how this `Deck` is shuffled
+     */
+    pub fn shuffle_style(&self) -> ShuffleStyle {
+        self.shuffle
+    }
+}
+
+// make a shoe mode printable
+impl Display for ShoeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShoeMode::Shoe => write!(f, "hand-shuffled shoe"),
+            ShoeMode::Continuous { buffer } => write!(f, "continuous shuffler holding {} cards", buffer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::shuffle::HumanShuffle;
+
+    fn deck(decks: usize, variant: Variant) -> Deck {
+        Deck::new(decks, variant, ShoeMode::Shoe, ShuffleStyle::Perfect)
+    }
+
+    #[test]
+    fn stacked_cards_are_drawn_in_order() {
+        let mut deck = deck(1, Variant::Classic);
+        let stacked = Hand::from_codes("AS KH 8D 8C 10S 2H");
+        deck.stack(stacked.cards()).unwrap();
+
+        for card in stacked.cards() {
+            let drawn = deck.draw_pile.draw().unwrap();
+            assert_eq!(drawn.code(), card.code());
+        }
+    }
+
+    #[test]
+    fn stacking_keeps_the_shoe_size() {
+        let mut deck = deck(2, Variant::Classic);
+        deck.stack(Hand::from_codes("AS AS KH QD").cards()).unwrap();
+        assert_eq!(deck.remaining(), 104);
+
+        // every card is still there exactly twice
+        let mut codes: Vec<String> = deck.draw_pile.cards.iter().map(|card| card.code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), 52);
+        for code in codes {
+            assert_eq!(deck.draw_pile.cards.iter().filter(|card| card.code() == code).count(), 2);
+        }
+    }
+
+    #[test]
+    fn stacking_gathers_the_discards() {
+        let mut deck = deck(1, Variant::Classic);
+        let mut hand = Hand::new();
+        for _ in 0..10 {
+            hand.draw_from(&mut deck);
+        }
+        deck.discard_hand(&mut hand);
+
+        deck.stack(Hand::from_codes("5C 6C").cards()).unwrap();
+        assert_eq!(deck.remaining(), 52);
+        assert_eq!(deck.draw_pile.draw().unwrap().code(), "5C");
+        assert_eq!(deck.draw_pile.draw().unwrap().code(), "6C");
+    }
+
+    #[test]
+    fn cards_not_in_the_shoe_are_refused() {
+        // spanish 21 has no tens
+        let mut deck = deck(1, Variant::Spanish21);
+        match deck.stack(Hand::from_codes("AS 10H").cards()) {
+            Err(ScenarioError::NotInShoe { card, wanted: 1, in_shoe: 0 }) => assert_eq!(card, "10H"),
+            _ => panic!("a ten was stacked into a spanish 21 shoe"),
+        }
+        assert_eq!(deck.remaining(), 48);
+    }
+
+    #[test]
+    fn extra_copies_are_refused() {
+        let mut deck = deck(1, Variant::Classic);
+        match deck.stack(Hand::from_codes("AS KD AS").cards()) {
+            Err(ScenarioError::NotInShoe { card, wanted: 2, in_shoe: 1 }) => assert_eq!(card, "AS"),
+            _ => panic!("a second ace of spades was stacked into one deck"),
+        }
+        assert_eq!(deck.remaining(), 52);
+    }
+
+    // deal `count` cards and throw them in the discards, as a round would
+    fn deal_and_discard(deck: &mut Deck, count: usize) {
+        let mut hand = Hand::new();
+        for _ in 0..count {
+            hand.draw_from(deck);
+        }
+        deck.discard_hand(&mut hand);
+    }
+
+    #[test]
+    fn continuous_shuffler_returns_discards_past_its_buffer() {
+        let mut deck = Deck::new(1, Variant::Classic, ShoeMode::Continuous { buffer: 8 }, ShuffleStyle::Perfect);
+        deal_and_discard(&mut deck, 20);
+        let held: Vec<String> = deck.discard_pile.cards[12..].iter().map(|card| card.code()).collect();
+
+        deck.end_round();
+        assert_eq!(deck.remaining(), 44);
+        assert_eq!(deck.discard_pile.cards.iter().map(|card| card.code()).collect::<Vec<_>>(), held);
+
+        // later rounds push the oldest held cards through
+        deal_and_discard(&mut deck, 4);
+        deck.end_round();
+        assert_eq!(deck.remaining(), 44);
+        assert_eq!(deck.discard_pile.cards[..4].iter().map(|card| card.code()).collect::<Vec<_>>(), held[4..]);
+
+        // discards that fit in the buffer stay there
+        let mut deck = Deck::new(1, Variant::Classic, ShoeMode::Continuous { buffer: 8 }, ShuffleStyle::Perfect);
+        deal_and_discard(&mut deck, 6);
+        deck.end_round();
+        assert_eq!(deck.remaining(), 46);
+    }
+
+    #[test]
+    fn continuous_shuffler_never_runs_the_shoe_out() {
+        let mut deck = Deck::new(1, Variant::Classic, ShoeMode::Continuous { buffer: 0 }, ShuffleStyle::Perfect);
+        for _ in 0..100 {
+            deal_and_discard(&mut deck, 10);
+            deck.end_round();
+            assert_eq!(deck.remaining(), 52);
+        }
+    }
+
+    #[test]
+    fn shoe_is_shuffled_once_the_cut_card_comes_out() {
+        let mut deck = Deck::new(1, Variant::Classic, ShoeMode::Shoe, ShuffleStyle::Human(HumanShuffle::TYPICAL));
+        let cut_at = deck.cut_at;
+        assert!(cut_at > 0);
+
+        deal_and_discard(&mut deck, 52 - cut_at - 1);
+        deck.end_round();
+        assert_eq!(deck.remaining(), cut_at + 1);
+
+        deal_and_discard(&mut deck, 1);
+        deck.end_round();
+        assert_eq!(deck.remaining(), 52);
+        assert!(deck.discard_pile.cards.is_empty());
+    }
 }
@@ -1,16 +1,65 @@
 use std::fmt::Display;
 
-use crate::util::util::format_vec_string;
+use serde::{Deserialize, Serialize};
 
-use super::{card::{Card, CardFace}, deck::Deck};
+use crate::game::money::Money;
+
+use super::{card::{Card, CardFace}, deck::Deck, theme::format_cards};
+
+/**
+ * A summary of a hand's total. Displays as `soft 17`, `hard 12`, `blackjack`,
+ * or `bust 24`
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct HandTotal {
+    pub best: u32,          // the highest total up to 21, or the lowest total if busted
+    pub soft: bool,         // an ace counts as 11 in the best total
+    pub pair: Option<u32>,  // the rank of a two card pair
+    pub blackjack: bool,
+    pub bust: bool,
+    pub cards: usize,
+}
+
+/**
+ * Which two card hands can be split
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SplitRule {
+    // both cards have the same rank, so K-K but not K-Q
+    #[default]
+    SameRank,
+    // same rank, or any two cards worth 10
+    AnyTen,
+}
+
+/**
+ * How a hand ranks in Pontoon, from lowest to highest.
+ * Hands of the same points tie
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PontoonRank {
+    Bust,
+    Points(u32),
+    FiveCardTrick,  // five cards without busting
+    Pontoon,        // 21 in two cards
+}
 
 #[derive(Clone)]
 /**
- * A `Hand` consists of a list of `Card`s and a status of if doubled down
+ * A `Hand` consists of a list of `Card`s, the amount bet on it, the seat it
+ * is played from, how many times its bet was doubled or bought again,
+ * statuses of if surrendered or split, and which of its stakes the house put
+ * up
  */
 pub struct Hand {
     cards: Vec<Card>,
-    doubled: bool,
+    bet: Money,
+    seat: usize,
+    buys: usize,
+    surrendered: bool,
+    split: bool,
+    free_doubled: bool,
+    free_split: bool,
 }
 
 impl Hand {
@@ -20,7 +69,13 @@ impl Hand {
     pub fn new() -> Hand {
         Hand {
             cards: vec![],
-            doubled: false,
+            bet: Money::ZERO,
+            seat: 0,
+            buys: 0,
+            surrendered: false,
+            split: false,
+            free_doubled: false,
+            free_split: false,
         }
     }
 
@@ -31,7 +86,7 @@ impl Hand {
      */
     pub fn draw_from(&mut self, deck: &mut Deck) -> Card {
         // draw a card
-        return match deck.draw_pile.draw() {
+        match deck.draw_pile.draw() {
             Some(c) => {
                 // add the card to hand and return
                 self.cards.push(c);
@@ -59,9 +114,18 @@ impl Hand {
      * Takes an optional to allow for soft None handling
      */
     pub fn give_card(&mut self, card: Option<Card>) {
-        match card {
-            Some(c) => self.cards.push(c),
-            _ => (),
+        if let Some(c) = card {
+            self.cards.push(c);
+        }
+    }
+
+    /**
+     * Swap the card at `ndx` with the card at the same place in `other`.
+     * Does nothing if either hand has no card there
+     */
+    pub fn swap_card(&mut self, other: &mut Hand, ndx: usize) {
+        if let (Some(a), Some(b)) = (self.cards.get_mut(ndx), other.cards.get_mut(ndx)) {
+            std::mem::swap(a, b);
         }
     }
 
@@ -116,6 +180,49 @@ impl Hand {
         *self.filter_value().iter().max().unwrap_or(&0)
     }
 
+    /**
+     * Gets a summary of the hand's total, including whether it is soft, a
+     * pair, a blackjack, or busted
+     */
+    pub fn total(&self) -> HandTotal {
+        // the lowest total counts every ace as 1. any higher valid total
+        // counts one as 11
+        let values = self.value();
+        let lowest = values[0];
+        let best = self.true_value();
+        let bust = best == 0;
+
+        let pair = match self.cards.as_slice() {
+            [a, b] if a.val == b.val => Some(a.val),
+            _ => None,
+        };
+
+        HandTotal {
+            best: if bust { lowest } else { best },
+            soft: !bust && best != lowest,
+            pair,
+            blackjack: self.is_blackjack(),
+            bust,
+            cards: self.cards.len(),
+        }
+    }
+
+    /**
+     * Gets all cards in hand, in the order they were drawn
+     */
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /**
+     * Draw the hand with only its first `shown` cards face up, in the
+     * current theme
+     */
+    pub fn display_hidden(&self, shown: usize) -> String {
+        let shown = shown.min(self.cards.len());
+        format_cards(&self.cards[..shown], self.cards.len() - shown)
+    }
+
     /**
      * Gets the first card in hand. 
      * Panics if no cards in hand.
@@ -127,10 +234,10 @@ impl Hand {
     /**
      * Returns whether the hand is a blackjack.
      * Is blackjack if and only if there are 2 cards that add to a true value of
-     * 21
+     * 21, and the hand was not split
      */
     pub fn is_blackjack(&self) -> bool {
-        self.true_value() == 21 && self.cards.len() == 2
+        self.true_value() == 21 && self.cards.len() == 2 && !self.split
     }
 
     /**
@@ -142,43 +249,215 @@ impl Hand {
         self.true_value() == 0
     }
 
+    /**
+     * Gets the rank of the hand in Pontoon. A pontoon beats a five-card trick,
+     * which beats any total of points
+     */
+    pub fn pontoon_rank(&self) -> PontoonRank {
+        if self.is_busted() {
+            PontoonRank::Bust
+        } else if self.is_blackjack() {
+            PontoonRank::Pontoon
+        } else if self.cards.len() >= 5 {
+            PontoonRank::FiveCardTrick
+        } else {
+            PontoonRank::Points(self.true_value())
+        }
+    }
+
     /**
      * Returns whether the hand can be split during a game of Blackjack.
-     * Is able to be split if and only if the hand has two cards and both cards
-     * have the same value
+     * Is able to be split if and only if the hand has two cards that match
+     * under `rule`
      */
-    pub fn is_splittable(&self) -> bool {
+    pub fn is_splittable(&self, rule: SplitRule) -> bool {
         // ensure length of 2
         if self.cards.len() != 2 {
             return false;
         }
 
-        // check that the two values are the same
-        return match (self.cards.get(0), self.cards.get(1)) {
-            (Some(a), Some(b)) => a.val == b.val,
+        // check that the two cards match
+        match (self.cards.first(), self.cards.get(1), rule) {
+            (Some(a), Some(b), SplitRule::SameRank) => a.val == b.val,
+            (Some(a), Some(b), SplitRule::AnyTen)   => a.val == b.val || (a.is_ten_value() && b.is_ten_value()),
             _ => false,
         }
     }
 
+    /**
+     * Set the amount bet on the hand. Does not include the extra stake from
+     * doubling down
+     */
+    pub fn set_bet(&mut self, bet: Money) {
+        self.bet = bet;
+    }
+
+    /**
+     * Get the amount bet on the hand
+     */
+    pub fn bet(&self) -> Money {
+        self.bet
+    }
+
+    /**
+     * Set the seat the hand is played from. Hands split from it keep the
+     * seat
+     */
+    pub fn set_seat(&mut self, seat: usize) {
+        self.seat = seat;
+    }
+
+    /**
+     * Get the seat the hand is played from
+     */
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
     /**
      * Set whether or not the hand has been doubled
      */
     pub fn set_doubled(&mut self, doubled: bool) {
-        self.doubled = doubled;
+        self.buys = doubled as usize;
     }
 
     /**
      * Check whether or not the hand has been doubled
      */
     pub fn is_doubled(&self) -> bool {
-        self.doubled
+        self.buys > 0
+    }
+
+    /**
+     * Put the bet up again for another card. Pontoon hands may buy more
+     * than once
+     */
+    pub fn buy(&mut self) {
+        self.buys += 1;
+    }
+
+    /**
+     * Get how many times the bet was put up again after the deal
+     */
+    pub fn buys(&self) -> usize {
+        self.buys
+    }
+
+    /**
+     * Get the total staked on the hand: the bet, plus the bet again for
+     * each double or buy
+     */
+    pub fn stake(&self) -> Money {
+        self.bet * (1 + self.buys)
+    }
+
+    /**
+     * Set whether or not the hand has been surrendered
+     */
+    pub fn set_surrendered(&mut self, surrendered: bool) {
+        self.surrendered = surrendered;
+    }
+
+    /**
+     * Check whether or not the hand has been surrendered
+     */
+    pub fn is_surrendered(&self) -> bool {
+        self.surrendered
+    }
+
+    /**
+     * Set whether or not the hand was split from a pair
+     */
+    pub fn set_split(&mut self, split: bool) {
+        self.split = split;
+    }
+
+    /**
+     * Set whether or not the house put up the doubling stake
+     */
+    pub fn set_free_doubled(&mut self, free: bool) {
+        self.free_doubled = free;
+    }
+
+    /**
+     * Check whether or not the house put up the doubling stake
+     */
+    pub fn is_free_doubled(&self) -> bool {
+        self.free_doubled
+    }
+
+    /**
+     * Set whether or not the house put up the bet of this hand when it was
+     * split off
+     */
+    pub fn set_free_split(&mut self, free: bool) {
+        self.free_split = free;
+    }
+
+    /**
+     * Check whether or not the house put up the bet of this hand when it was
+     * split off
+     */
+    pub fn is_free_split(&self) -> bool {
+        self.free_split
+    }
+
+    /**
+     * Get the part of the stake on this hand put up by the house.
+     * The player collects winnings on it, but it is never paid back
+     */
+    pub fn free_stake(&self) -> Money {
+        let mut free = Money::ZERO;
+        if self.free_split {
+            free += self.bet;
+        }
+        if self.free_doubled {
+            free += self.bet;
+        }
+        free
     }
 }
 
-// make a hand printable
+#[cfg(test)]
+impl Hand {
+    /**
+     * Create a hand holding the cards given as codes, ex: `AS KH`.
+     * Panics on a code that is not a card
+     */
+    pub fn from_codes(codes: &str) -> Hand {
+        let mut hand = Hand::new();
+        for code in codes.split_whitespace() {
+            hand.give_card(Some(code.parse().unwrap()));
+        }
+        hand
+    }
+}
+
+// make a hand printable in the current theme
 impl Display for Hand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // show the hand as a comma separated list of cards
-        write!(f, "{}", format_vec_string(&self.cards))
+        write!(f, "{}", format_cards(&self.cards, 0))
+    }
+}
+
+// make a hand total printable. ex: soft 17
+impl Display for HandTotal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.blackjack, self.bust, self.soft) {
+            (true, _, _)  => write!(f, "blackjack"),
+            (_, true, _)  => write!(f, "bust {}", self.best),
+            (_, _, true)  => write!(f, "soft {}", self.best),
+            (_, _, false) => write!(f, "hard {}", self.best),
+        }
+    }
+}
+
+// make a split rule printable
+impl Display for SplitRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            SplitRule::SameRank => "same rank",
+            SplitRule::AnyTen => "same rank or any two tens",
+        })
     }
 }
@@ -1,5 +1,8 @@
-mod suit;
-mod card;
+pub mod suit;
+pub mod card;
 
 pub mod deck;
 pub mod hand;
+pub mod scenario;
+pub mod shuffle;
+pub mod theme;
@@ -0,0 +1,67 @@
+use std::{fmt::Display, fs, io};
+
+use super::card::{Card, CardParseError};
+
+/**
+ * Reasons a scenario file could not be loaded
+ */
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Card { line: usize, token: String, err: CardParseError },
+    Empty,
+    // a card stacked more times than the shoe holds it, ex: a ten in
+    // spanish 21
+    NotInShoe { card: String, wanted: usize, in_shoe: usize },
+}
+
+/**
+ * Load a scenario file: an exact card order to stack the shoe with.
+ * See `parse_scenario` for the format
+ */
+pub fn load_scenario(path: &str) -> Result<Vec<Card>, ScenarioError> {
+    let text = fs::read_to_string(path).map_err(ScenarioError::Io)?;
+    parse_scenario(&text)
+}
+
+/**
+ * Parse a scenario: cards separated by whitespace, drawn in the order listed,
+ * ex: `AS KH 8D 8C`.
+ * Anything after a `//` on a line is a comment
+ */
+pub fn parse_scenario(text: &str) -> Result<Vec<Card>, ScenarioError> {
+    let mut cards = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        // drop comments
+        let line = match line.split_once("//") {
+            Some((cards, _)) => cards,
+            None             => line,
+        };
+
+        for token in line.split_whitespace() {
+            match token.parse() {
+                Ok(card) => cards.push(card),
+                Err(err) => return Err(ScenarioError::Card { line: i + 1, token: String::from(token), err }),
+            }
+        }
+    }
+
+    match cards.is_empty() {
+        true  => Err(ScenarioError::Empty),
+        false => Ok(cards),
+    }
+}
+
+// describe a scenario error for the player
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioError::Io(err) => write!(f, "could not read scenario: {}", err),
+            ScenarioError::Card { line, token, err } => write!(f, "line {}: {:?} is not a card: {}", line, token, err),
+            ScenarioError::Empty => write!(f, "scenario has no cards"),
+            ScenarioError::NotInShoe { card, in_shoe: 0, .. } => write!(f, "the shoe has no {}", card),
+            ScenarioError::NotInShoe { card, wanted, in_shoe } => write!(f, "{} is stacked {} times, but the shoe has only {}", card, wanted, in_shoe),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use rand::{prelude::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::card::Card;
+
+/**
+ * How a pile of cards is shuffled
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShuffleStyle {
+    // a perfectly uniform shuffle
+    #[default]
+    Perfect,
+    // a simulated dealer shuffle, which leaves some order behind
+    Human(HumanShuffle),
+}
+
+/**
+ * The steps of a simulated dealer shuffle.
+ * The shoe is riffled `riffles` times, strip cut, then box shuffled.
+ * After shuffling, a cut card is placed for the next shuffle
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HumanShuffle {
+    pub riffles: usize,         // riffle passes
+    pub clumping: u32,          // percent chance the next card of a riffle falls from the same half
+    pub strip_cuts: usize,      // packets pulled off in the strip cut. 0 to skip
+    pub box_shuffle: bool,      // reverse the order of the shoe's quarters
+    pub plug: bool,             // place undealt cards into the discards as a block
+    pub penetration: u32,       // percent of the shoe dealt before the cut card
+    pub cut_spread: usize,      // how many cards either side the cut card can miss by
+}
+
+impl HumanShuffle {
+    /**
+     * A typical casino shuffle
+     */
+    pub const TYPICAL: HumanShuffle = HumanShuffle {
+        riffles: 3,
+        clumping: 30,
+        strip_cuts: 4,
+        box_shuffle: true,
+        plug: true,
+        penetration: 75,
+        cut_spread: 6,
+    };
+
+    /**
+     * Shuffle `cards` by each step of the dealer shuffle
+     */
+    pub fn shuffle(&self, cards: &mut Vec<Card>) {
+        let mut rng = rand::rng();
+
+        for _ in 0..self.riffles {
+            riffle(cards, self.clumping, &mut rng);
+        }
+        if self.strip_cuts > 0 {
+            strip_cut(cards, self.strip_cuts, &mut rng);
+        }
+        if self.box_shuffle {
+            box_shuffle(cards);
+        }
+    }
+
+    /**
+     * Place the cut card in a shoe of `size` cards.
+     * Returns how many cards are left behind the cut card; the shoe is
+     * shuffled once no more than this many remain
+     */
+    pub fn place_cut_card(&self, size: usize) -> usize {
+        let target = size * (100 - self.penetration.min(100) as usize) / 100;
+        let miss = rand::rng().random_range(0..=self.cut_spread * 2);
+        (target + miss).saturating_sub(self.cut_spread).min(size)
+    }
+}
+
+impl ShuffleStyle {
+    /**
+     * Shuffle `cards` in this style
+     */
+    pub fn shuffle(&self, cards: &mut Vec<Card>) {
+        match self {
+            ShuffleStyle::Perfect => cards.shuffle(&mut rand::rng()),
+            ShuffleStyle::Human(human) => human.shuffle(cards),
+        }
+    }
+}
+
+/**
+ * Riffle shuffle: cut near the middle, then drop cards from alternating
+ * halves. Each card has a `clumping` percent chance of falling from the same
+ * half as the last, leaving runs of cards together
+ */
+fn riffle(cards: &mut Vec<Card>, clumping: u32, rng: &mut impl Rng) {
+    // cut within a few cards of the middle
+    let half = cards.len() / 2;
+    let wobble = cards.len() / 10;
+    let cut = rng.random_range(half.saturating_sub(wobble)..=(half + wobble).min(cards.len()));
+    let right = cards.split_off(cut);
+    let left = std::mem::take(cards);
+
+    // drop cards from the bottom of each half until both are used
+    let (mut l, mut r) = (left.into_iter().peekable(), right.into_iter().peekable());
+    let mut from_left = rng.random_bool(0.5);
+    while l.peek().is_some() || r.peek().is_some() {
+        let card = match (from_left, l.peek().is_some(), r.peek().is_some()) {
+            (true, true, _) | (false, true, false) => l.next(),
+            _ => r.next(),
+        };
+        cards.extend(card);
+
+        // switch halves unless the cards clump
+        if !rng.random_bool(clumping.min(100) as f64 / 100.0) {
+            from_left = !from_left;
+        }
+    }
+}
+
+/**
+ * Strip cut: pull `strips` packets of rough size off the top one after
+ * another, reversing their order
+ */
+fn strip_cut(cards: &mut Vec<Card>, strips: usize, rng: &mut impl Rng) {
+    let size = cards.len() / strips;
+    let mut packets = vec![];
+
+    // the last packet takes whatever is left
+    while !cards.is_empty() {
+        let take = match packets.len() + 1 < strips {
+            true  => rng.random_range(size / 2..=size + size / 2).min(cards.len()),
+            false => cards.len(),
+        };
+        let top = cards.len() - take;
+        packets.push(cards.split_off(top));
+    }
+
+    // the first packet pulled ends up on the bottom
+    for packet in packets {
+        cards.extend(packet);
+    }
+}
+
+/**
+ * Box shuffle: split the pile into quarters and stack them in reverse order
+ */
+fn box_shuffle(cards: &mut Vec<Card>) {
+    if cards.is_empty() {
+        return;
+    }
+
+    let quarter = cards.len().div_ceil(4);
+    let boxed: Vec<Card> = cards.chunks(quarter).rev().flat_map(|chunk| chunk.to_vec()).collect();
+    *cards = boxed;
+}
+
+// make a shuffle style printable
+impl Display for ShuffleStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShuffleStyle::Perfect => write!(f, "perfect shuffle"),
+            ShuffleStyle::Human(human) => write!(f, "dealer shuffle ({} riffles, {}% clumping, {}% penetration)", human.riffles, human.clumping, human.penetration),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::suit::Suit;
+
+    fn deck() -> Vec<Card> {
+        (0..52).map(|i| Card::from_rank(i % 13 + 1, Suit::from_val((i / 13) as usize))).collect()
+    }
+
+    fn codes(cards: &[Card]) -> Vec<String> {
+        let mut codes: Vec<String> = cards.iter().map(|card| card.code()).collect();
+        codes.sort();
+        codes
+    }
+
+    #[test]
+    fn each_step_keeps_every_card() {
+        let mut rng = rand::rng();
+        let mut cards = deck();
+        riffle(&mut cards, 30, &mut rng);
+        assert_eq!(codes(&cards), codes(&deck()));
+        strip_cut(&mut cards, 4, &mut rng);
+        assert_eq!(codes(&cards), codes(&deck()));
+        box_shuffle(&mut cards);
+        assert_eq!(codes(&cards), codes(&deck()));
+        HumanShuffle::TYPICAL.shuffle(&mut cards);
+        assert_eq!(codes(&cards), codes(&deck()));
+    }
+
+    #[test]
+    fn box_shuffle_reverses_the_quarters() {
+        let mut cards = deck();
+        box_shuffle(&mut cards);
+        assert_eq!(cards[0].code(), deck()[39].code());
+        assert_eq!(cards[13].code(), deck()[26].code());
+        assert_eq!(cards[51].code(), deck()[12].code());
+    }
+
+    #[test]
+    fn cut_card_is_placed_near_the_penetration() {
+        for _ in 0..100 {
+            let cut = HumanShuffle::TYPICAL.place_cut_card(312);
+            assert!((72..=84).contains(&cut), "cut card left {} cards", cut);
+        }
+    }
+}
@@ -1,6 +1,8 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-#[derive(Copy, Clone)]
+use super::{card::CardParseError, theme::suit_symbol};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 /**
  * Each suit option
  */
@@ -8,18 +10,10 @@ pub enum Suit {
     Spades, Hearts, Diamonds, Clubs
 }
 
-// make a suit printable
+// make a suit printable in the current theme
 impl Display for Suit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // simple match of various symbols
-        write!(f, "{}", 
-            String::from(match self {
-                Suit::Spades => "@",
-                Suit::Hearts => "#",
-                Suit::Diamonds => "$",
-                Suit::Clubs => "%",
-            }
-        ))
+        write!(f, "{}", suit_symbol(self))
     }
 }
 
@@ -36,4 +30,28 @@ impl Suit {
             _ => Suit::Clubs,
         }
     }
+
+    /**
+     * Returns whether the suit is red (Hearts or Diamonds)
+     */
+    pub fn is_red(&self) -> bool {
+        matches!(self, Suit::Hearts | Suit::Diamonds)
+    }
+}
+
+impl FromStr for Suit {
+    type Err = CardParseError;
+
+    /**
+     * Parse a suit from its letter, ex: `S` or `h`, or its printed symbol
+     */
+    fn from_str(s: &str) -> Result<Suit, CardParseError> {
+        match s.to_ascii_uppercase().as_str() {
+            "S" | "@" => Ok(Suit::Spades),
+            "H" | "#" => Ok(Suit::Hearts),
+            "D" | "$" => Ok(Suit::Diamonds),
+            "C" | "%" => Ok(Suit::Clubs),
+            _ => Err(CardParseError::InvalidSuit(String::from(s))),
+        }
+    }
 }
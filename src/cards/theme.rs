@@ -0,0 +1,343 @@
+use std::{fmt::Display, sync::{atomic::{AtomicU8, Ordering}, Mutex}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::term::{color_enabled, four_color, screen_reader, visible_width};
+
+use super::{card::{Card, CardFace}, suit::Suit};
+
+/**
+ * How cards are drawn on screen
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardTheme {
+    // colored `@K`, `#10`
+    #[default]
+    Symbols,
+    // colored suit glyphs, `♠K`, `♥10`
+    Unicode,
+    // colored playing card code points, `🂮`
+    Cards,
+    // boxed multi-line cards
+    Art,
+    // uncolored `KS`, `10H`, for logs
+    Plain,
+}
+
+// the theme used by every card drawn, as its index in `CardTheme::ALL`
+static THEME: AtomicU8 = AtomicU8::new(0);
+
+/**
+ * The color of each suit, as a 256 color code. Codes can be found here:
+ * https://gist.github.com/fnky/458719343aabd01cfb17a3a4f7296797
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SuitColors {
+    pub spades: u8,
+    pub hearts: u8,
+    pub diamonds: u8,
+    pub clubs: u8,
+}
+
+// the colors cards are drawn in, standard then four-color
+static COLORS: Mutex<(SuitColors, SuitColors)> = Mutex::new((SuitColors::STANDARD, SuitColors::FOUR_COLOR));
+
+// lines taken by a row of art cards
+const ART_LINES: usize = 5;
+
+// columns taken by each art card, with the space after it
+const ART_WIDTH: usize = 8;
+
+impl CardTheme {
+    /**
+     * All themes, in the order they are listed in settings
+     */
+    pub const ALL: [CardTheme; 5] = [CardTheme::Symbols, CardTheme::Unicode, CardTheme::Cards, CardTheme::Art, CardTheme::Plain];
+}
+
+impl SuitColors {
+    /**
+     * Each suit has a unique color
+     */
+    pub const STANDARD: SuitColors = SuitColors {
+        spades: 51,     // Cyan
+        hearts: 206,    // Mid pink
+        diamonds: 171,  // Magenta
+        clubs: 159,     // Light blue
+    };
+
+    /**
+     * Colors that stay apart for colorblind players
+     */
+    pub const FOUR_COLOR: SuitColors = SuitColors {
+        spades: 255,    // White
+        hearts: 196,    // Red
+        diamonds: 33,   // Blue
+        clubs: 40,      // Green
+    };
+}
+
+/**
+ * Set the colors cards are drawn in, for the standard and four-color decks
+ */
+pub fn set_suit_colors(standard: SuitColors, four_color: SuitColors) {
+    *COLORS.lock().unwrap() = (standard, four_color);
+}
+
+/**
+ * Set the theme used to draw every card
+ */
+pub fn set_theme(theme: CardTheme) {
+    let ndx = CardTheme::ALL.iter().position(|t| *t == theme).unwrap_or(0);
+    THEME.store(ndx as u8, Ordering::Relaxed);
+}
+
+/**
+ * Get the theme used to draw every card
+ */
+pub fn theme() -> CardTheme {
+    CardTheme::ALL[THEME.load(Ordering::Relaxed) as usize]
+}
+
+/**
+ * Get the symbol of a suit in the current theme
+ */
+pub fn suit_symbol(suit: &Suit) -> &'static str {
+    match (theme(), suit) {
+        (CardTheme::Symbols | CardTheme::Art, Suit::Spades)   => "@",
+        (CardTheme::Symbols | CardTheme::Art, Suit::Hearts)   => "#",
+        (CardTheme::Symbols | CardTheme::Art, Suit::Diamonds) => "$",
+        (CardTheme::Symbols | CardTheme::Art, Suit::Clubs)    => "%",
+        (CardTheme::Unicode | CardTheme::Cards, Suit::Spades)   => "♠",
+        (CardTheme::Unicode | CardTheme::Cards, Suit::Hearts)   => "♥",
+        (CardTheme::Unicode | CardTheme::Cards, Suit::Diamonds) => "♦",
+        (CardTheme::Unicode | CardTheme::Cards, Suit::Clubs)    => "♣",
+        (CardTheme::Plain, Suit::Spades)   => "S",
+        (CardTheme::Plain, Suit::Hearts)   => "H",
+        (CardTheme::Plain, Suit::Diamonds) => "D",
+        (CardTheme::Plain, Suit::Clubs)    => "C",
+    }
+}
+
+/**
+ * Draw a single card on one line in the current theme.
+ * Art cards are drawn as their symbols when alone.
+ * Screen readers get the card in words, ex: `eight of clubs`
+ */
+pub fn format_card(card: &Card) -> String {
+    if screen_reader() {
+        return spoken(card);
+    }
+
+    match theme() {
+        CardTheme::Symbols | CardTheme::Unicode | CardTheme::Art => color(card, &format!("{}{}", suit_symbol(&card.suit), rank(card))),
+        CardTheme::Cards => color(card, &playing_card(card).to_string()),
+        CardTheme::Plain => format!("{}{}", rank(card), suit_symbol(&card.suit)),
+    }
+}
+
+/**
+ * Draw `cards` face up, followed by `hidden` face down cards, in the current
+ * theme.
+ * Art cards are drawn side by side starting on the next line; other themes
+ * are a comma separated list
+ */
+pub fn format_cards(cards: &[Card], hidden: usize) -> String {
+    if theme() == CardTheme::Art && !screen_reader() {
+        return art_cards(cards, hidden);
+    }
+
+    // face down cards
+    let back = match (theme(), screen_reader()) {
+        (_, true)              => "face down card",
+        (CardTheme::Cards, _)  => "\u{1F0A0}",
+        _ => "??",
+    };
+
+    let mut drawn: Vec<String> = cards.iter().map(format_card).collect();
+    drawn.extend((0..hidden).map(|_| String::from(back)));
+    drawn.join(", ")
+}
+
+/**
+ * Draw `cards` face up, followed by `hidden` face down cards, as rows no
+ * wider than `width` columns in the current theme.
+ * Cards that do not fit move down to the next row
+ */
+pub fn card_rows(cards: &[Card], hidden: usize, width: usize) -> Vec<String> {
+    // art cards wrap as whole rows of boxes
+    if theme() == CardTheme::Art && !screen_reader() {
+        let per_row = ((width + 1) / ART_WIDTH).max(1);
+        let total = cards.len() + hidden;
+        let mut rows = vec![];
+        for start in (0..total).step_by(per_row) {
+            let end = (start + per_row).min(total);
+            let up = &cards[start.min(cards.len())..end.min(cards.len())];
+            let boxes = art_cards(up, end - start - up.len());
+            rows.extend(boxes.lines().skip(1).map(String::from));
+        }
+        return rows;
+    }
+
+    // face down cards
+    let back = match theme() {
+        CardTheme::Cards => "\u{1F0A0}",
+        _ => "??",
+    };
+
+    // fill each row with as many cards as fit
+    let mut drawn: Vec<String> = cards.iter().map(format_card).collect();
+    drawn.extend((0..hidden).map(|_| String::from(back)));
+    let mut rows = vec![String::new()];
+    for card in drawn {
+        let row = rows.last_mut().unwrap();
+        if row.is_empty() {
+            row.push_str(&card);
+        } else if visible_width(row) + 2 + visible_width(&card) <= width {
+            row.push_str(", ");
+            row.push_str(&card);
+        } else {
+            rows.push(card);
+        }
+    }
+
+    rows
+}
+
+/**
+ * Get the rank of a card as printed. ex: `K`, `10`
+ */
+fn rank(card: &Card) -> String {
+    match &card.face {
+        Some(face) => format!("{:?}", face),
+        None => card.val.to_string(),
+    }
+}
+
+/**
+ * Get a card in words, ex: `ace of spades`
+ */
+fn spoken(card: &Card) -> String {
+    let rank = match card.val {
+        1  => "ace",
+        2  => "two",
+        3  => "three",
+        4  => "four",
+        5  => "five",
+        6  => "six",
+        7  => "seven",
+        8  => "eight",
+        9  => "nine",
+        10 => "ten",
+        11 => "jack",
+        12 => "queen",
+        _  => "king",
+    };
+    let suit = match card.suit {
+        Suit::Spades => "spades",
+        Suit::Hearts => "hearts",
+        Suit::Diamonds => "diamonds",
+        Suit::Clubs => "clubs",
+    };
+
+    format!("{} of {}", rank, suit)
+}
+
+/**
+ * Color text by the suit of `card`. Each suit has a unique color, set with
+ * `set_suit_colors`.
+ * The four-color deck uses colors that stay apart for colorblind players
+ */
+fn color(card: &Card, text: &str) -> String {
+    if !color_enabled() {
+        return String::from(text);
+    }
+
+    let colors = match (*COLORS.lock().unwrap(), four_color()) {
+        ((standard, _), false) => standard,
+        ((_, four), true)      => four,
+    };
+    let color = match card.suit {
+        Suit::Spades => colors.spades,
+        Suit::Hearts => colors.hearts,
+        Suit::Diamonds => colors.diamonds,
+        Suit::Clubs => colors.clubs,
+    };
+
+    format!("[38;5;{}m{}[0m", color, text)
+}
+
+/**
+ * Get the Unicode playing card of `card`. Each suit has a block of 16 code
+ * points, with a knight between the jack and queen
+ */
+fn playing_card(card: &Card) -> char {
+    let suit = match card.suit {
+        Suit::Spades => 0x1F0A0,
+        Suit::Hearts => 0x1F0B0,
+        Suit::Diamonds => 0x1F0C0,
+        Suit::Clubs => 0x1F0D0,
+    };
+    let rank = match card.face {
+        Some(CardFace::Q) => 13,
+        Some(CardFace::K) => 14,
+        _ => card.val,
+    };
+
+    char::from_u32(suit + rank).unwrap_or('?')
+}
+
+/**
+ * Draw cards as boxes side by side, starting on a new line. ex:
+ * +-----+ +-----+
+ * |K    | |/////|
+ * |  @  | |/////|
+ * |    K| |/////|
+ * +-----+ +-----+
+ */
+fn art_cards(cards: &[Card], hidden: usize) -> String {
+    let mut rows = vec![String::new(); ART_LINES];
+
+    // face up cards show their rank in the corners and suit in the middle
+    for card in cards {
+        let rank = rank(card);
+        let lines = [
+            String::from("+-----+"),
+            format!("|{}|", color(card, &format!("{:<5}", rank))),
+            format!("|  {}  |", color(card, suit_symbol(&card.suit))),
+            format!("|{}|", color(card, &format!("{:>5}", rank))),
+            String::from("+-----+"),
+        ];
+        for (row, line) in rows.iter_mut().zip(lines) {
+            row.push_str(&line);
+            row.push(' ');
+        }
+    }
+
+    // face down cards are hatched
+    for _ in 0..hidden {
+        let lines = ["+-----+", "|/////|", "|/////|", "|/////|", "+-----+"];
+        for (row, line) in rows.iter_mut().zip(lines) {
+            row.push_str(line);
+            row.push(' ');
+        }
+    }
+
+    // drop the trailing space so text can follow the last row
+    let rows: Vec<&str> = rows.iter().map(|row| row.trim_end()).collect();
+    format!("\n{}", rows.join("\n"))
+}
+
+// make a theme printable
+impl Display for CardTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            CardTheme::Symbols => "Symbols",
+            CardTheme::Unicode => "Unicode suits",
+            CardTheme::Cards => "Unicode cards",
+            CardTheme::Art => "ASCII art",
+            CardTheme::Plain => "Plain text",
+        })
+    }
+}
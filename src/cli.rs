@@ -0,0 +1,446 @@
+use std::env;
+
+use crate::{cards::{deck::ShoeMode, shuffle::{HumanShuffle, ShuffleStyle}}, game::{bank::{GameBank, Transaction}, chips::SeatBet, config::with_config, game::Game, gamestate::{min_bet, GameState}, money::Money, settings::{DealSpeed, GameSettings}, strategy::basic_strategy, variant::Variant}, net::{bot::run_bot, client::join, server::{run_server, DEFAULT_ADDRESS}}, util::term::{set_quiet, tprintln}};
+
+/**
+ * How to run the game from the command line
+ */
+pub const USAGE: &str = "Usage: blackjack [command] [options]
+
+Commands:
+  (none)                      open the menu
+  play [table options]        start a game right away, skipping settings
+  practice [table options]    play where money does not count, with keys to
+                              undo the last play and see what each play would
+                              have done
+  stats                       show the balance and banking totals
+  history [--export csv]      list recent transactions, or write them as CSV
+  reset-bank                  reset the balance
+  sim [table options] [--rounds N] [--bankroll AMOUNT]
+                              play rounds by basic strategy and report the return
+  bot [table options] [--rounds N] [--bankroll AMOUNT] [--exec COMMAND]
+                              let a bot play over JSON lines on stdin and stdout,
+                              or run COMMAND as the bot, where --hands is the
+                              most seats it may bet on
+  server [table options] [--listen ADDRESS]
+                              host a table for players to join, where --hands is
+                              the most seats each may take
+  join ADDRESS [--name NAME] [--hands N]
+                              sit at a hosted table, ex: join 127.0.0.1:7878
+  help                        show this message
+
+Table options:
+  --rules NAME                a preset from the config, vegas, single-deck, or a
+                              game mode such as classic or spanish21
+  --decks N                   decks in the shoe
+  --hands N                   hands to play
+  --bet AMOUNT                bet on each hand
+
+Display and input options:
+  --input FILE                read commands from FILE, one per line
+  --scenario FILE             stack the shoe from FILE
+  --screen-reader             linear output without colors
+  --four-color                use the four-color deck";
+
+/**
+ * A command given on the command line
+ */
+pub enum Command {
+    Play(TableArgs),
+    Practice(TableArgs),
+    Stats,
+    History { csv: bool },
+    ResetBank,
+    Sim { table: TableArgs, rounds: usize, bankroll: Option<Money> },
+    Bot { table: TableArgs, rounds: usize, bankroll: Option<Money>, command: Option<String> },
+    Server { table: TableArgs, address: String },
+    Join { address: String, name: String, seats: usize },
+    Help,
+}
+
+/**
+ * Changes to the table given on the command line. Anything not given is kept
+ * from the rules, or from the last settings
+ */
+#[derive(Default)]
+pub struct TableArgs {
+    rules: Option<String>,
+    decks: Option<usize>,
+    hands: Option<usize>,
+    bet: Option<Money>,
+}
+
+// options read by `main` for every command, and whether each takes a value
+const SHARED_OPTIONS: [(&str, bool); 4] = [("--input", true), ("--scenario", true), ("--screen-reader", false), ("--four-color", false)];
+
+impl Command {
+    /**
+     * Returns whether the command plays at the table, and so reads input
+     */
+    pub fn is_interactive(&self) -> bool {
+        matches!(self, Command::Play(_) | Command::Practice(_) | Command::Join { .. })
+    }
+
+    /**
+     * Returns whether the menu is opened once the command is done
+     */
+    pub fn opens_menu(&self) -> bool {
+        matches!(self, Command::Play(_) | Command::Practice(_))
+    }
+}
+
+impl TableArgs {
+    /**
+     * Build the settings to play with: the named rules, or else `last`, with
+     * the deck and hand counts changed.
+     * Returns an error if a value is out of range
+     */
+    fn settings(&self, last: Option<&GameSettings>) -> Result<GameSettings, String> {
+        let mut settings = match &self.rules {
+            Some(name) => rules(name)?,
+            None => last.cloned().unwrap_or_default(),
+        };
+
+        let (max_decks, max_hands) = with_config(|config| (config.table.max_decks, config.table.max_hands));
+        if let Some(decks) = self.decks {
+            if !(1..=max_decks).contains(&decks) {
+                return Err(format!("--decks must be 1-{}, not {}", max_decks, decks));
+            }
+            settings.deck_count = decks;
+        }
+        if let Some(hands) = self.hands {
+            if !(1..=max_hands).contains(&hands) {
+                return Err(format!("--hands must be 1-{}, not {}", max_hands, hands));
+            }
+            settings.hand_count = hands;
+        }
+
+        // a shuffler holds at most a quarter of the decks
+        if let ShoeMode::Continuous { buffer } = settings.shoe {
+            settings.shoe = ShoeMode::Continuous { buffer: buffer.min(settings.deck_count * 13) };
+        }
+
+        Ok(settings)
+    }
+
+    /**
+     * Get the bet for each hand, the minimum if not given, with what it costs
+     * to place on every hand of the table in `settings`.
+     * Returns an error if it is below the minimum, or too large to count
+     */
+    fn bet(&self, settings: &GameSettings) -> Result<(Money, Money), String> {
+        let hands = settings.hand_count * settings.variant.hands_per_seat();
+        let bet = match self.bet {
+            Some(bet) if bet < min_bet() => return Err(format!("--bet must be at least {}, not {}", min_bet(), bet)),
+            Some(bet) => bet,
+            None => min_bet(),
+        };
+        match bet.checked_mul(hands) {
+            Some(cost) => Ok((bet, cost)),
+            None => Err(format!("--bet of {} on {} hands is too large", bet, hands)),
+        }
+    }
+}
+
+/**
+ * Read the command from the arguments after the program name. Options shared
+ * by every command are skipped, they are read by `main`.
+ * Returns None when no command is given, or an error for anything not
+ * understood
+ */
+pub fn parse(args: &[String]) -> Result<Option<Command>, String> {
+    // the command is the first argument that is not a shared option
+    let mut at = 0;
+    while let Some(arg) = args.get(at) {
+        match SHARED_OPTIONS.iter().find(|(shared, _)| shared == arg) {
+            Some((_, true))  => at += 2,
+            Some((_, false)) => at += 1,
+            None if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg)),
+            None => break,
+        }
+    }
+    let Some(name) = args.get(at).map(String::as_str) else {
+        return match at > args.len() {
+            true  => Err(format!("{} needs a value", args[args.len() - 1])),
+            false => Ok(None),
+        };
+    };
+    let rest = &args[at + 1..];
+
+    let mut table = TableArgs::default();
+    let mut rounds = 1000;
+    let mut bankroll = None;
+    let mut csv = false;
+    let mut address = None;
+    let mut command = None;
+    let mut player = env::var("USER").unwrap_or_default();
+
+    let mut i = 0;
+    while i < rest.len() {
+        let option = rest[i].as_str();
+        let value = || rest.get(i + 1).map(String::as_str).ok_or(format!("{} needs a value", option));
+
+        match (name, option) {
+            // options that take a value
+            ("play" | "practice" | "sim" | "bot" | "server", "--rules") => table.rules = Some(value()?.to_string()),
+            ("play" | "practice" | "sim" | "bot" | "server", "--decks") => table.decks = Some(number(option, value()?)?),
+            ("play" | "practice" | "sim" | "bot" | "server" | "join", "--hands") => table.hands = Some(number(option, value()?)?),
+            ("play" | "practice" | "sim", "--bet") => table.bet = Some(amount(option, value()?)?),
+            ("sim" | "bot", "--rounds") => rounds = number(option, value()?)?,
+            ("sim" | "bot", "--bankroll") => bankroll = Some(amount(option, value()?)?),
+            ("bot", "--exec") => command = Some(value()?.to_string()),
+            ("history", "--export") => match value()? {
+                "csv" => csv = true,
+                other => return Err(format!("cannot export as `{}`, only csv", other)),
+            },
+            ("server", "--listen") => address = Some(value()?.to_string()),
+            ("join", "--name") => player = value()?.to_string(),
+            // the address to join
+            ("join", _) if !option.starts_with("--") && address.is_none() => {
+                address = Some(option.to_string());
+                i += 1;
+                continue;
+            },
+            // shared options, skipping their value
+            _ => match SHARED_OPTIONS.iter().find(|(shared, _)| *shared == option) {
+                Some((_, true)) => {
+                    value()?;
+                },
+                Some((_, false)) => {
+                    i += 1;
+                    continue;
+                },
+                None => return Err(format!("unknown option `{}` for {}", option, name)),
+            },
+        }
+        i += 2;
+    }
+
+    match name {
+        "play"       => Ok(Some(Command::Play(table))),
+        "practice"   => Ok(Some(Command::Practice(table))),
+        "stats"      => Ok(Some(Command::Stats)),
+        "history"    => Ok(Some(Command::History { csv })),
+        "reset-bank" => Ok(Some(Command::ResetBank)),
+        "sim"        => Ok(Some(Command::Sim { table, rounds, bankroll })),
+        "bot"        => Ok(Some(Command::Bot { table, rounds, bankroll, command })),
+        "server"     => Ok(Some(Command::Server { table, address: address.unwrap_or(String::from(DEFAULT_ADDRESS)) })),
+        "join"       => match address {
+            Some(address) => Ok(Some(Command::Join { address, name: player, seats: table.hands.unwrap_or(1) })),
+            None => Err(String::from("join needs an address, ex: join 127.0.0.1:7878")),
+        },
+        "help"       => Ok(Some(Command::Help)),
+        _ => Err(format!("unknown command `{}`", name)),
+    }
+}
+
+/**
+ * Parse a whole number given to `option`
+ */
+fn number(option: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("{} needs a whole number, not `{}`", option, value))
+}
+
+/**
+ * Parse an amount of money given to `option`
+ */
+fn amount(option: &str, value: &str) -> Result<Money, String> {
+    value.parse().map_err(|_| format!("{} needs an amount such as 100 or $12.50, not `{}`", option, value))
+}
+
+/**
+ * Find rules by name, ignoring case, spaces, and dashes: a preset from the
+ * config, one of the built in tables, or a game mode with the default table
+ */
+fn rules(name: &str) -> Result<GameSettings, String> {
+    let simplify = |name: &str| -> String {
+        name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
+    };
+    let key = simplify(name);
+
+    // presets from the config come first
+    let presets = with_config(|config| config.presets.clone());
+    if let Some(preset) = presets.iter().find(|preset| simplify(&preset.name) == key) {
+        return Ok(preset.settings.clone());
+    }
+
+    // built in tables are dealt from a shoe by hand, without side bets
+    let mut settings = GameSettings::default();
+    match key.as_str() {
+        "vegas" => {
+            settings.shuffle = ShuffleStyle::Human(HumanShuffle::TYPICAL);
+            settings.side_bets = vec![];
+        },
+        "singledeck" => {
+            settings.deck_count = 1;
+            settings.shuffle = ShuffleStyle::Human(HumanShuffle::TYPICAL);
+            settings.side_bets = vec![];
+        },
+        _ => match Variant::ALL.iter().find(|variant| simplify(&variant.to_string()) == key || simplify(&format!("{:?}", variant)) == key) {
+            Some(variant) => settings.variant = *variant,
+            None => {
+                let mut names = vec![String::from("vegas"), String::from("single-deck")];
+                names.extend(Variant::ALL.iter().map(|variant| format!("{:?}", variant).to_lowercase()));
+                names.extend(presets.iter().map(|preset| preset.name.clone()));
+                return Err(format!("unknown rules `{}`, choose from: {}", name, names.join(", ")));
+            },
+        },
+    }
+
+    Ok(settings)
+}
+
+/**
+ * Run a command against the saved state.
+ * Returns an error to show if it could not be run
+ */
+pub fn run(command: Command, gamestate: &mut GameState) -> Result<(), String> {
+    match command {
+        Command::Play(table) => play(table, gamestate),
+        Command::Practice(table) => practice(table, gamestate),
+        Command::Stats => {
+            println!("Balance: {}\n{}", gamestate.bank.get_balance(), gamestate.bank.stats());
+            Ok(())
+        },
+        Command::History { csv: true } => {
+            println!("{}", Transaction::CSV_HEADER);
+            for transaction in gamestate.bank.transactions() {
+                println!("{}", transaction.csv_row());
+            }
+            Ok(())
+        },
+        Command::History { csv: false } => {
+            for (i, transaction) in gamestate.bank.transactions().iter().rev().enumerate() {
+                tprintln!("{}: {}", i + 1, transaction);
+            }
+            Ok(())
+        },
+        Command::ResetBank => {
+            gamestate.bank.reset_balance();
+            gamestate.save_state()?;
+            println!("Balance reset to {}. You now have {} resets.", gamestate.bank.get_balance(), gamestate.bank.get_resets());
+            Ok(())
+        },
+        Command::Sim { table, rounds, bankroll } => simulate(table, rounds, bankroll, gamestate),
+        Command::Bot { table, rounds, bankroll, command } => {
+            let settings = table.settings(gamestate.settings())?;
+            let bankroll = bankroll.unwrap_or(with_config(|config| config.bank.starting_balance));
+            let (played, balance) = run_bot(&settings, rounds, bankroll, command.as_deref()).map_err(|err| format!("bot: {}", err))?;
+
+            // stdout is the bot's own without a command to run
+            let summary = format!("Played {} rounds. Balance: {} -> {}", played, bankroll, balance);
+            match command {
+                Some(_) => println!("{}", summary),
+                None    => eprintln!("{}", summary),
+            }
+            Ok(())
+        },
+        Command::Server { table, address } => {
+            let settings = table.settings(gamestate.settings())?;
+            run_server(&address, &settings).map_err(|err| format!("{}: {}", address, err))
+        },
+        Command::Join { address, name, seats } => join(&address, &name, seats).map_err(|err| format!("{}: {}", address, err)),
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+    }
+}
+
+/**
+ * Start a game with the table given, skipping the settings questions. The
+ * bet is placed on every seat to start the first round
+ */
+fn play(table: TableArgs, gamestate: &mut GameState) -> Result<(), String> {
+    let settings = table.settings(gamestate.settings())?;
+    let (bet, _) = table.bet(&settings)?;
+    let seats = settings.hand_count;
+    gamestate.use_settings(settings);
+    if table.bet.is_some() {
+        gamestate.bank.last_bets = vec![SeatBet { main: bet, side: vec![] }; seats];
+    }
+
+    if !gamestate.can_start() {
+        return Err(format!("{} cannot cover the minimum bet on {} hands", gamestate.bank.get_balance(), seats));
+    }
+    gamestate.start_game();
+    Ok(())
+}
+
+/**
+ * Start a practice game with the table given, on a bank of its own that is
+ * never saved. The bet is placed on every seat to start the first round
+ */
+fn practice(table: TableArgs, gamestate: &GameState) -> Result<(), String> {
+    let settings = table.settings(gamestate.settings())?;
+    let (bet, _) = table.bet(&settings)?;
+    let seats = settings.hand_count;
+    let mut practice = gamestate.for_practice(settings);
+    if table.bet.is_some() {
+        practice.bank.last_bets = vec![SeatBet { main: bet, side: vec![] }; seats];
+    }
+
+    if !practice.can_start() {
+        return Err(format!("{} cannot cover the minimum bet on {} hands", practice.bank.get_balance(), seats));
+    }
+    practice.start_game();
+    Ok(())
+}
+
+/**
+ * Play `rounds` rounds by basic strategy with a bank of its own, starting
+ * with `bankroll` or the starting balance, and report the return.
+ * The saved bank is not touched. Stops early if the bank runs out
+ */
+fn simulate(table: TableArgs, rounds: usize, bankroll: Option<Money>, gamestate: &GameState) -> Result<(), String> {
+    let mut settings = table.settings(gamestate.settings())?;
+    settings.speed = DealSpeed::Instant;
+    let (bet, cost) = table.bet(&settings)?;
+
+    // the balance and the total staked must count every round's stake
+    let mut bank = match bankroll {
+        Some(balance) => GameBank::with_balance(balance),
+        None => GameBank::new(),
+    };
+    if cost.checked_mul(rounds).and_then(|staked| staked.checked_add(bank.get_balance())).is_none() {
+        return Err(format!("--bankroll of {} with {} rounds at {} each is too large", bank.get_balance(), rounds, cost));
+    }
+    let start = bank.get_balance();
+    let mut game = Game::new(&settings);
+    game.set_strategy(basic_strategy);
+
+    // every hand bets the same, without side bets
+    let per_seat = settings.variant.hands_per_seat();
+    let bets = vec![SeatBet { main: bet, side: vec![] }; settings.hand_count];
+
+    set_quiet(true);
+    let mut played = 0;
+    let mut staked = Money::ZERO;
+    while played < rounds && bank.get_balance() >= cost {
+        bank.buy(cost, settings.hand_count * per_seat);
+        let result = game.play(&bets, bank.get_balance());
+        staked += cost + result.spent;
+        if result.bought > 0 {
+            bank.buy(result.spent, result.bought);
+        }
+        if !result.payout.is_zero() {
+            bank.win(result.payout);
+        }
+        played += 1;
+    }
+    set_quiet(false);
+
+    // the return is what came back for each dollar staked
+    let net = bank.get_balance() - start;
+    println!("{} with {} decks, {} hands at {} each, by basic strategy.", settings.variant, settings.deck_count, settings.hand_count, bet);
+    match played < rounds {
+        true  => println!("Ran out of money after {} of {} rounds.", played, rounds),
+        false => println!("Played {} rounds.", played),
+    }
+    println!("Balance: {} -> {} ({}{})", start, bank.get_balance(), if net >= Money::ZERO { "+" } else { "" }, net);
+    println!("{}", bank.stats());
+    if staked > Money::ZERO {
+        println!("Return: {:.2}% of {} staked", (net + staked).cents() as f64 / staked.cents() as f64 * 100.0, staked);
+    }
+    Ok(())
+}
@@ -1,17 +1,20 @@
 use std::{cmp, fmt::Display};
 
-use prompted::input;
 use serde::{Deserialize, Serialize};
 
-use crate::util::input::read_one_char;
+use crate::util::{input::read_one_char, keys::{action_for, help, key_label, Action, Context}, term::{tinput, tprintln}};
+
+use super::{chips::SeatBet, config::with_config, money::Money, sidebets::SideBetKind};
 
 /**
- * Bank / Money handler. Holds balance, bets, and a banking history
+ * Bank / Money handler. Holds balance, the last bet on each seat, and a
+ * banking history
  */
 #[derive(Serialize, Deserialize)]
 pub struct GameBank {
-    balance: usize,
-    pub cur_bet: usize,
+    balance: Money,
+    #[serde(default)]
+    pub last_bets: Vec<SeatBet>,
     history: BankHistory,
 }
 
@@ -22,8 +25,8 @@ pub struct GameBank {
 #[derive(Serialize, Deserialize)]
 pub struct BankHistory {
     resets: usize,
-    total_spent: usize,
-    total_earned: usize,
+    total_spent: Money,
+    total_earned: Money,
     hands_bought: usize,
     recent_transactions: Vec<Transaction>,
 }
@@ -33,30 +36,41 @@ pub struct BankHistory {
  */
 #[derive(Serialize, Deserialize)]
 pub struct Transaction {
-    pub amount: usize,
+    pub amount: Money,
     pub typ: TransactionType,
-    pub balance: usize,
+    pub balance: Money,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum TransactionType {
-    SPEND,
-    EARN,
-    RESET,
+    Spend,
+    Earn,
+    Reset,
+    SideSpend(SideBetKind),
+    SideEarn(SideBetKind),
 }
 
 impl GameBank {
     /**
-     * Create a new empty bank. Typically used if no save was loaded
+     * Create a new empty bank with the configured starting balance.
+     * Typically used if no save was loaded
      */
     pub fn new() -> GameBank {
+        GameBank::with_balance(with_config(|config| config.bank.starting_balance))
+    }
+
+    /**
+     * Create a new empty bank holding `balance`
+     */
+    pub fn with_balance(balance: Money) -> GameBank {
         GameBank {
-            balance: 1000,
-            cur_bet: 0,
+            balance,
+            last_bets: vec![],
             history: BankHistory {
                 resets: 0,
-                total_spent: 0,
-                total_earned: 0,
+                total_spent: Money::ZERO,
+                total_earned: Money::ZERO,
                 hands_bought: 0,
                 recent_transactions: vec![],
             }
@@ -64,42 +78,53 @@ impl GameBank {
     }
 
     /**
-     * Pay for `hands` hands based on the `cur_bet`
+     * Pay `amount` for `hands` hands
      */
-    pub fn buy(&mut self, hands: usize) {
-        let amount = self.cur_bet * hands;
+    pub fn buy(&mut self, amount: Money, hands: usize) {
         self.balance -= amount;
         self.history.hands_bought += hands;
-        self.history.add_transaction(self.balance, amount, TransactionType::SPEND);
+        self.history.add_transaction(self.balance, amount, TransactionType::Spend);
     }
 
     /**
-     * Gain payout for winning `weight` payouts.
-     * 1 weight corresponds to `cur_bet`.
-     * Returns the amount gained
+     * Pay `amount` to place a side bet of `kind`
      */
-    pub fn win(&mut self, weight: f32) -> usize {
-        // multiply weight and bet, round to whole by type casting
-        let amount = (self.cur_bet as f32 * weight) as usize;
+    pub fn buy_side_bet(&mut self, kind: SideBetKind, amount: Money) {
+        self.balance -= amount;
+        self.history.add_transaction(self.balance, amount, TransactionType::SideSpend(kind));
+    }
 
+    /**
+     * Gain a payout of `amount` from a side bet of `kind`
+     */
+    pub fn win_side_bet(&mut self, kind: SideBetKind, amount: Money) {
+        self.balance += amount;
+        self.history.add_transaction(self.balance, amount, TransactionType::SideEarn(kind));
+    }
+
+    /**
+     * Gain a payout of `amount`.
+     * Returns the amount gained
+     */
+    pub fn win(&mut self, amount: Money) -> Money {
         self.balance += amount;
-        self.history.add_transaction(self.balance, amount, TransactionType::EARN);
+        self.history.add_transaction(self.balance, amount, TransactionType::Earn);
         amount
     }
 
     /**
-     * Reset the current balance.
+     * Reset the current balance to the configured starting balance.
      * Typically used when completely out of money
      */
     pub fn reset_balance(&mut self) {
-        self.balance = 1000;
-        self.history.add_transaction(self.balance, 0, TransactionType::RESET);
+        self.balance = with_config(|config| config.bank.starting_balance);
+        self.history.add_transaction(self.balance, Money::ZERO, TransactionType::Reset);
     }
 
     /**
      * Get the current balance
      */
-    pub fn get_balance(&self) -> usize {
+    pub fn get_balance(&self) -> Money {
         self.balance
     }
 
@@ -110,6 +135,21 @@ impl GameBank {
         self.history.resets
     }
 
+    /**
+     * Get the recent transactions, oldest first
+     */
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.history.recent_transactions
+    }
+
+    /**
+     * Describe the banking history totals, one per line
+     */
+    pub fn stats(&self) -> String {
+        format!("Total Resets: {}\nTotal Won: {}\nTotal Spent: {}\nHands Bought: {}",
+            self.get_resets(), self.history.total_earned, self.history.total_spent, self.history.hands_bought)
+    }
+
     /**
      * Runs the history UI
      */
@@ -128,29 +168,28 @@ impl GameBank {
             let visible = &elements[pos..end];
 
             // print transactions and title
-            println!("\n[2JTransaction History (h for help):");
+            tprintln!("\n[2JTransaction History ({} for help):", key_label(Action::Help));
             let mut ndx = pos;
             for transaction in visible {
                 ndx += 1;
-                println!("{ndx}: {transaction}");
+                tprintln!("{ndx}: {transaction}");
             }
 
-            // character inputs
-            match read_one_char() {
+            // inputs by key binding
+            match action_for(Context::History, read_one_char()) {
                 // quit
-                'q' => break,
+                Some(Action::Quit) => break,
                 // scroll down
-                'j' => pos = cmp::min(pos + 1, size.checked_sub(10).unwrap_or(0)),
+                Some(Action::Next) => pos = cmp::min(pos + 1, size.saturating_sub(10)),
                 // scroll up
-                'k' => pos = pos.checked_sub(1).unwrap_or(0),
+                Some(Action::Previous) => pos = pos.saturating_sub(1),
                 // show stats
-                's' => {
-                    input!("[2JTotal Resets: {}\nTotal Won: {}\nTotal Spent: {}\nHands Bought: {}\n\nEnter to continue...",
-                        self.get_resets(), self.history.total_earned, self.history.total_spent, self.history.hands_bought);
+                Some(Action::Stats) => {
+                    tinput!("[2J{}\n\nEnter to continue...", self.stats());
                 },
                 // help
-                'h' => {
-                    input!("[2JShows the (up to) 128 most recent transactions. Navigate with your keyboard:\n\th - help\n\tq - quit\n\tj - scroll down\n\tk - scroll up\n\ts - stats\n\nEnter to continue...");
+                Some(Action::Help) => {
+                    tinput!("[2JShows the (up to) {} most recent transactions. Navigate with your keyboard:\n{}\n\nEnter to continue...", with_config(|config| config.bank.history), help(Context::History));
                 },
                 // ignore unregistered inputs
                 _ => {},
@@ -162,19 +201,20 @@ impl GameBank {
 impl BankHistory {
     /**
      * Add a transaction to recent transactions.
-     * Holds the 128 most recent transactions
+     * Holds the most recent transactions, 128 unless configured
      */
-    fn add_transaction(&mut self, balance: usize, amount: usize, typ: TransactionType) {
-        // limit 128
-        if self.recent_transactions.len() >= 128 {
+    fn add_transaction(&mut self, balance: Money, amount: Money, typ: TransactionType) {
+        // limit to the configured history, dropping the oldest
+        let limit = with_config(|config| config.bank.history);
+        while self.recent_transactions.len() >= limit {
             self.recent_transactions.remove(0);
         }
 
         // add amount to spent or earned
         match typ {
-            TransactionType::SPEND => self.total_spent += amount,
-            TransactionType::EARN => self.total_earned += amount,
-            TransactionType::RESET => self.resets += 1,
+            TransactionType::Spend | TransactionType::SideSpend(_) => self.total_spent += amount,
+            TransactionType::Earn | TransactionType::SideEarn(_) => self.total_earned += amount,
+            TransactionType::Reset => self.resets += 1,
         }
 
         // add to list
@@ -182,21 +222,53 @@ impl BankHistory {
     }
 }
 
+impl Transaction {
+    /**
+     * The columns of `csv_row`
+     */
+    pub const CSV_HEADER: &str = "type,side_bet,amount,balance";
+
+    /**
+     * Write the transaction as a row of comma separated values, with amounts
+     * in plain dollars. ex: `earn,,37.50,1037.50`
+     */
+    pub fn csv_row(&self) -> String {
+        let (typ, kind) = match self.typ {
+            TransactionType::Spend => ("spend", None),
+            TransactionType::Earn => ("earn", None),
+            TransactionType::Reset => ("reset", None),
+            TransactionType::SideSpend(kind) => ("side_spend", Some(kind)),
+            TransactionType::SideEarn(kind) => ("side_earn", Some(kind)),
+        };
+        let kind = kind.map_or(String::new(), |kind| kind.to_string());
+        let plain = |amount: Money| amount.to_string().replacen('$', "", 1);
+        format!("{},{},{},{}", typ, kind, plain(self.amount), plain(self.balance))
+    }
+}
+
 impl Display for Transaction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", 
             match self.typ {
-                TransactionType::SPEND => {
+                TransactionType::Spend => {
                     // [RED]-amount[/RED] -> new_bal 
-                    format!("[38;5;196m-${}[0m -> ${}", self.amount, self.balance)
+                    format!("[38;5;196m-{}[0m -> {}", self.amount, self.balance)
                 },
-                TransactionType::EARN => {
+                TransactionType::Earn => {
                     // [GREEN]+amount[/GREEN] -> new_bal 
-                    format!("[38;5;40m+${}[0m -> ${}", self.amount, self.balance)
+                    format!("[38;5;40m+{}[0m -> {}", self.amount, self.balance)
                 },
-                TransactionType::RESET => {
+                TransactionType::Reset => {
                     // [YELLOW]Bank Reset[/YELLOW] -> new_bal 
-                    format!("[38;5;214mBank Reset[0m -> ${}", self.balance)
+                    format!("[38;5;214mBank Reset[0m -> {}", self.balance)
+                },
+                TransactionType::SideSpend(kind) => {
+                    // [RED]-amount[/RED] (side bet) -> new_bal
+                    format!("[38;5;196m-{}[0m ({}) -> {}", self.amount, kind, self.balance)
+                },
+                TransactionType::SideEarn(kind) => {
+                    // [GREEN]+amount[/GREEN] (side bet) -> new_bal
+                    format!("[38;5;40m+{}[0m ({}) -> {}", self.amount, kind, self.balance)
                 },
             },
         )
@@ -0,0 +1,349 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::{input::read_one_char, keys::{action_for, help, key_label, Action, Context}, term::{tinput, tprint, tprintln}};
+
+use super::{money::Money, sidebets::SideBetKind};
+
+/**
+ * The action that adds each chip, in the order of `CHIP_VALUES`
+ */
+const CHIP_ACTIONS: [Action; 6] = [Action::Chip1, Action::Chip2, Action::Chip3, Action::Chip4, Action::Chip5, Action::Chip6];
+
+/**
+ * Chip denominations available at the table, smallest first.
+ * Hotkeys `1`-`6` map onto these in order
+ */
+pub const CHIP_VALUES: [Money; 6] = [
+    Money::from_dollars(1),
+    Money::from_dollars(5),
+    Money::from_dollars(25),
+    Money::from_dollars(100),
+    Money::from_dollars(500),
+    Money::from_dollars(1000),
+];
+
+/**
+ * Gets the display color of a chip. Colors roughly follow casino chips.
+ * color codes can be found
+ * here: https://gist.github.com/fnky/458719343aabd01cfb17a3a4f7296797
+ */
+fn chip_color(chip: Money) -> u8 {
+    match CHIP_VALUES.iter().position(|c| *c == chip) {
+        Some(0) => 255, // White
+        Some(1) => 196, // Red
+        Some(2) => 40,  // Green
+        Some(3) => 245, // Grey (black chips would be invisible)
+        Some(4) => 129, // Purple
+        _       => 220, // Yellow
+    }
+}
+
+/**
+ * A stack of chips in the order they were placed
+ */
+#[derive(Clone)]
+pub struct ChipStack {
+    chips: Vec<Money>,
+}
+
+/**
+ * The bets placed on one seat: the main bet and any side bets
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SeatBet {
+    pub main: Money,
+    pub side: Vec<(SideBetKind, Money)>,
+}
+
+impl SeatBet {
+    /**
+     * Get the total staked on the seat when the main bet is placed on
+     * `hands_per_seat` hands, including side bets
+     */
+    pub fn cost(&self, hands_per_seat: usize) -> Money {
+        self.main * hands_per_seat + self.side.iter().map(|(_, amount)| *amount).sum()
+    }
+
+    /**
+     * Get the amount staked on a side bet, 0 if not placed
+     */
+    pub fn side_bet(&self, kind: SideBetKind) -> Money {
+        self.side.iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, amount)| *amount)
+            .unwrap_or(Money::ZERO)
+    }
+}
+
+/**
+ * A single change to a seat's stacks, kept so it can be undone.
+ * Each holds the seat and the betting spot on that seat
+ */
+enum ChipMove {
+    Added(usize, usize),
+    Removed(usize, usize, Money),
+    Cleared(usize, usize, ChipStack),
+}
+
+impl ChipStack {
+    /**
+     * Create an empty stack
+     */
+    pub fn new() -> ChipStack {
+        ChipStack {
+            chips: vec![],
+        }
+    }
+
+    /**
+     * Create a stack worth `amount`, using the largest chips possible.
+     * Any cents that cannot be made from chips are left off
+     */
+    pub fn from_amount(amount: Money) -> ChipStack {
+        let mut stack = ChipStack::new();
+        let (counts, _) = chip_breakdown(amount);
+
+        // place the smallest chips first so the biggest sit on top
+        for (chip, count) in counts.iter().rev() {
+            for _ in 0..*count {
+                stack.add(*chip);
+            }
+        }
+
+        stack
+    }
+
+    /**
+     * Place a chip on top of the stack
+     */
+    pub fn add(&mut self, chip: Money) {
+        self.chips.push(chip);
+    }
+
+    /**
+     * Take the top chip off the stack.
+     * Returns None if the stack is empty
+     */
+    pub fn take(&mut self) -> Option<Money> {
+        self.chips.pop()
+    }
+
+    /**
+     * Get the total worth of the stack
+     */
+    pub fn total(&self) -> Money {
+        self.chips.iter().copied().sum()
+    }
+
+    /**
+     * Returns whether there are no chips in the stack
+     */
+    pub fn is_empty(&self) -> bool {
+        self.chips.is_empty()
+    }
+}
+
+// show a stack grouped by denomination, biggest first. ex: $25x2 $5x1
+impl Display for ChipStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "[2m(no chips)[0m");
+        }
+
+        let groups: Vec<String> = CHIP_VALUES.iter().rev()
+            .map(|chip| (chip, self.chips.iter().filter(|c| *c == chip).count()))
+            .filter(|(_, count)| *count > 0)
+            .map(|(chip, count)| format_chip(*chip, count))
+            .collect();
+
+        write!(f, "{}", groups.join(" "))
+    }
+}
+
+/**
+ * Format `count` chips of one denomination, colored by chip. ex: $25x2
+ */
+fn format_chip(chip: Money, count: usize) -> String {
+    format!("[38;5;{}m${}x{}[0m", chip_color(chip), chip.dollars(), count)
+}
+
+/**
+ * Break an amount into the fewest chips possible.
+ * Returns the count of each chip used, biggest first, and any remaining
+ * amount too small for a chip
+ */
+pub fn chip_breakdown(amount: Money) -> (Vec<(Money, usize)>, Money) {
+    let mut remaining = amount;
+    let mut counts = vec![];
+
+    // greedily take the biggest chips that fit
+    for chip in CHIP_VALUES.iter().rev() {
+        let count = remaining.count_of(*chip);
+        if count > 0 {
+            counts.push((*chip, count));
+            remaining -= *chip * count;
+        }
+    }
+
+    (counts, remaining)
+}
+
+/**
+ * Format an amount as the chips it would be paid out in.
+ * ex: $137.50 -> $100x1 $25x1 $5x2 $1x2 + $0.50
+ */
+pub fn format_breakdown(amount: Money) -> String {
+    let (counts, remaining) = chip_breakdown(amount);
+    let mut parts: Vec<String> = counts.iter()
+        .map(|(chip, count)| format_chip(*chip, *count))
+        .collect();
+
+    // loose change is paid in coins
+    if !remaining.is_zero() {
+        parts.push(format!("+ {}", remaining));
+    }
+
+    parts.join(" ")
+}
+
+/**
+ * Runs the chip tray betting UI for `seats` seats.
+ * Each seat has a main bet spot, placed on each of its `hands_per_seat`
+ * hands, plus a spot for each of `side_bets`.
+ * Each main bet must hold at least `min_bet`, and all seats together cannot
+ * cost more than `balance`.
+ * Seats start with the stacks from `last_bets` if they are affordable.
+ * Returns the bets for each seat, or None if betting was cancelled
+ */
+pub fn run_betting_ui(balance: Money, seats: usize, hands_per_seat: usize, min_bet: Money, side_bets: &[SideBetKind], last_bets: &[SeatBet]) -> Option<Vec<SeatBet>> {
+    // build a stack for a spot on a seat from the last bets
+    let last_stack = |bet: &SeatBet, spot: usize| match spot {
+        0 => ChipStack::from_amount(bet.main),
+        _ => ChipStack::from_amount(bet.side_bet(side_bets[spot - 1])),
+    };
+
+    // start from the last bets if they can still be afforded
+    let last_total: Money = last_bets.iter().map(|bet| bet.cost(hands_per_seat)).sum();
+    let spots = side_bets.len() + 1;
+    let mut stacks: Vec<Vec<ChipStack>> = match last_bets.len() == seats && last_total <= balance {
+        true  => last_bets.iter().map(|bet| (0..spots).map(|spot| last_stack(bet, spot)).collect()).collect(),
+        false => vec![vec![ChipStack::new(); spots]; seats],
+    };
+
+    let mut history: Vec<ChipMove> = vec![];
+    let mut selected = 0;
+    let mut spot = 0;
+    let mut message = String::new();
+
+    // run until confirmed or cancelled
+    loop {
+        // main bets are paid once for each hand on the seat
+        let placed: Money = stacks.iter()
+            .map(|seat| seat[0].total() * hands_per_seat + seat[1..].iter().map(|s| s.total()).sum())
+            .sum();
+        let remaining = balance - placed;
+        let chip_cost = |chip: Money| match spot {
+            0 => chip * hands_per_seat,
+            _ => chip,
+        };
+
+        // show balance and the chip tray. unaffordable chips are dimmed
+        tprint!("\n[2JPlace your bets ({} for help):\nBalance: {}  Remaining: {}  Minimum per seat: {}\n\nChips: ", key_label(Action::Help), balance, remaining, min_bet);
+        for (chip, action) in CHIP_VALUES.iter().zip(CHIP_ACTIONS) {
+            match chip_cost(*chip) <= remaining {
+                true  => tprint!("{}:[{}] ", key_label(action), format_chip(*chip, 1)),
+                false => tprint!("[2m{}:[${}x1][0m ", key_label(action), chip.dollars()),
+            }
+        }
+        tprintln!("\n");
+
+        // show each seat's stacks and totals, bracketing the selected spot
+        let bracket = |seat: usize, n: usize| match seat == selected && n == spot {
+            true  => ("[", "]"),
+            false => ("", ""),
+        };
+        for (i, seat) in stacks.iter().enumerate() {
+            let marker = if i == selected { ">" } else { " " };
+            let main = match seat[0].total() >= min_bet {
+                true  => format!("[38;5;40m{}[0m", seat[0].total()),
+                false => format!("[38;5;196m{}[0m", seat[0].total()),
+            };
+            let (open, close) = bracket(i, 0);
+            tprint!("{} Seat {}: {}Main: {} ; {}{}", marker, i + 1, open, seat[0], main, close);
+
+            // side bets have no minimum
+            for (n, kind) in side_bets.iter().enumerate() {
+                let (open, close) = bracket(i, n + 1);
+                tprint!(" | {}{}: {} ; {}{}", open, kind, seat[n + 1], seat[n + 1].total(), close);
+            }
+            tprintln!();
+        }
+        tprintln!("\n{}", message);
+        message.clear();
+
+        // inputs by key binding
+        match action_for(Context::Bet, read_one_char()) {
+            // add a chip to the selected spot
+            Some(action @ (Action::Chip1 | Action::Chip2 | Action::Chip3 | Action::Chip4 | Action::Chip5 | Action::Chip6)) => {
+                let chip = CHIP_VALUES[CHIP_ACTIONS.iter().position(|a| *a == action).unwrap_or(0)];
+                if chip_cost(chip) <= remaining {
+                    stacks[selected][spot].add(chip);
+                    history.push(ChipMove::Added(selected, spot));
+                } else {
+                    message = format!("You cannot afford another {} chip.", chip);
+                }
+            },
+            // remove the top chip from the selected spot
+            Some(Action::RemoveChip) => {
+                if let Some(chip) = stacks[selected][spot].take() {
+                    history.push(ChipMove::Removed(selected, spot, chip));
+                }
+            },
+            // clear the selected spot
+            Some(Action::ClearSpot) if !stacks[selected][spot].is_empty() => {
+                history.push(ChipMove::Cleared(selected, spot, stacks[selected][spot].clone()));
+                stacks[selected][spot] = ChipStack::new();
+            },
+            // undo the last chip change
+            Some(Action::Undo) => {
+                match history.pop() {
+                    Some(ChipMove::Added(seat, n))          => _ = stacks[seat][n].take(),
+                    Some(ChipMove::Removed(seat, n, chip))  => stacks[seat][n].add(chip),
+                    Some(ChipMove::Cleared(seat, n, stack)) => stacks[seat][n] = stack,
+                    None => message = String::from("Nothing to undo."),
+                }
+            },
+            // switch between the main and side bet spots
+            Some(Action::SwitchSpot) => spot = (spot + 1) % spots,
+            // select the next seat
+            Some(Action::Next) => selected = (selected + 1).min(seats - 1),
+            // select the previous seat
+            Some(Action::Previous) => selected = selected.saturating_sub(1),
+            // confirm bets
+            Some(Action::PlaceBets) => {
+                match stacks.iter().position(|s| s[0].total() < min_bet) {
+                    Some(seat) => message = format!("Seat {} needs at least {}.", seat + 1, min_bet),
+                    None       => {
+                        return Some(stacks.iter().map(|seat| SeatBet {
+                            main: seat[0].total(),
+                            side: side_bets.iter().enumerate()
+                                .map(|(n, kind)| (*kind, seat[n + 1].total()))
+                                .filter(|(_, amount)| !amount.is_zero())
+                                .collect(),
+                        }).collect());
+                    },
+                }
+            },
+            // cancel
+            Some(Action::Quit) => return None,
+            // help, listing the bound keys
+            Some(Action::Help) => {
+                tinput!("[2JBuild a stack of chips on each seat. Navigate with your keyboard:\n{}\n\nEnter to continue...", help(Context::Bet));
+            },
+            // ignore unregistered inputs
+            _ => {},
+        }
+    }
+}
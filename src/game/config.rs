@@ -0,0 +1,314 @@
+use std::{collections::{BTreeMap, HashMap}, env, fs, io, path::PathBuf, sync::Mutex};
+
+use serde::Deserialize;
+
+use crate::{cards::{deck::ShoeMode, shuffle::ShuffleStyle, theme::SuitColors}, util::keys::KeyConfig};
+
+use super::{money::Money, settings::GameSettings, sidebets::{SideBetKind, SideBetRule}};
+
+/**
+ * Defaults read from `config.toml` in the XDG config dir. Every section is
+ * optional, missing values keep the built in defaults. ex:
+ *
+ * [bank]
+ * starting_balance = 1000
+ * history = 128
+ *
+ * [table]
+ * min_bet = 50
+ * max_hands = 7
+ * max_decks = 16
+ *
+ * [colors.standard]
+ * spades = 51
+ * hearts = 206
+ * diamonds = 171
+ * clubs = 159
+ *
+ * [keys]
+ * preset = "Vim"
+ * [keys.bindings]
+ * Hit = ["j", "enter"]
+ *
+ * [[preset]]
+ * name = "Vegas"
+ * deck_count = 6
+ * hand_count = 2
+ * charlie = { cards = 5, num = 1, den = 1 }
+ * side_bets = { PerfectPairs = "Standard" }
+ */
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bank: BankConfig,
+    pub table: TableConfig,
+    pub colors: ColorConfig,
+    pub keys: Option<KeyConfig>,        // replaces `keys.json` when given
+    #[serde(rename = "preset")]
+    pub presets: Vec<TablePreset>,
+}
+
+/**
+ * Money given to new and reset banks, and how much history is kept
+ */
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BankConfig {
+    pub starting_balance: Money,
+    pub history: usize,                 // most recent transactions kept
+}
+
+/**
+ * Limits of the table
+ */
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableConfig {
+    pub min_bet: Money,
+    pub max_hands: usize,
+    pub max_decks: usize,
+}
+
+/**
+ * Suit colors of the standard and four-color decks
+ */
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    pub standard: SuitColors,
+    pub four_color: SuitColors,
+}
+
+/**
+ * A named table, offered in the Play Game menu. Takes the same fields as
+ * saved settings, with side bets picked by paytable name
+ */
+#[derive(Clone, Deserialize)]
+pub struct TablePreset {
+    pub name: String,
+    #[serde(default)]
+    side_bets: HashMap<SideBetKind, String>,
+    #[serde(flatten)]
+    pub settings: GameSettings,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+// where the config file is kept, under the XDG config dir
+const CONFIG_FILE: &str = "blackjack/config.toml";
+
+// the most hands and decks a config can allow
+const HANDS_LIMIT: usize = 16;
+const DECKS_LIMIT: usize = 64;
+
+// the config in use. the defaults until loaded
+static CONFIG: Mutex<Option<Config>> = Mutex::new(None);
+
+impl Default for BankConfig {
+    fn default() -> BankConfig {
+        BankConfig {
+            starting_balance: Money::from_dollars(1000),
+            history: 128,
+        }
+    }
+}
+
+impl Default for TableConfig {
+    fn default() -> TableConfig {
+        TableConfig {
+            min_bet: Money::from_dollars(50),
+            max_hands: 7,
+            max_decks: 16,
+        }
+    }
+}
+
+impl Default for ColorConfig {
+    fn default() -> ColorConfig {
+        ColorConfig {
+            standard: SuitColors::STANDARD,
+            four_color: SuitColors::FOUR_COLOR,
+        }
+    }
+}
+
+impl Config {
+    /**
+     * Check every value is in range, and fill in each preset's side bets.
+     * Returns an error for each problem found
+     */
+    fn validate(&mut self) -> Vec<String> {
+        let mut errors = vec![];
+        let (bank, table) = (&self.bank, &self.table);
+
+        if table.min_bet <= Money::ZERO {
+            errors.push(format!("table.min_bet must be more than $0.00, not {}", table.min_bet));
+        }
+        if !(1..=HANDS_LIMIT).contains(&table.max_hands) {
+            errors.push(format!("table.max_hands must be 1-{}, not {}", HANDS_LIMIT, table.max_hands));
+        }
+        if !(1..=DECKS_LIMIT).contains(&table.max_decks) {
+            errors.push(format!("table.max_decks must be 1-{}, not {}", DECKS_LIMIT, table.max_decks));
+        }
+        if bank.starting_balance < table.min_bet {
+            errors.push(format!("bank.starting_balance of {} is less than the minimum bet of {}", bank.starting_balance, table.min_bet));
+        }
+        if bank.history == 0 {
+            errors.push(String::from("bank.history must keep at least 1 transaction"));
+        }
+
+        for (i, preset) in self.presets.iter().enumerate() {
+            let name = match preset.name.trim() {
+                ""   => format!("preset {}", i + 1),
+                name => format!("preset \"{}\"", name),
+            };
+            if preset.name.trim().is_empty() {
+                errors.push(format!("{} needs a name", name));
+            }
+            if self.presets[..i].iter().any(|other| other.name == preset.name) {
+                errors.push(format!("{} is named more than once", name));
+            }
+            errors.extend(preset.unknown.keys().map(|key| format!("{} has an unknown field `{}`", name, key)));
+            errors.extend(preset.check(table).into_iter().map(|err| format!("{} {}", name, err)));
+        }
+
+        // paytables are picked by name from those each side bet offers
+        for preset in self.presets.iter_mut() {
+            let mut rules = vec![];
+            for kind in SideBetKind::ALL {
+                let Some(wanted) = preset.side_bets.get(&kind) else {
+                    continue;
+                };
+                let paytables = kind.paytables();
+                match paytables.iter().find(|paytable| paytable.name.eq_ignore_ascii_case(wanted)) {
+                    Some(paytable) => rules.push(SideBetRule { kind, paytable: paytable.clone() }),
+                    None => {
+                        let names: Vec<&str> = paytables.iter().map(|paytable| paytable.name.as_str()).collect();
+                        errors.push(format!("preset \"{}\" has no {} paytable \"{}\", choose from: {}", preset.name, kind, wanted, names.join(", ")));
+                    },
+                }
+            }
+            preset.settings.side_bets = rules;
+        }
+
+        errors
+    }
+}
+
+impl TablePreset {
+    /**
+     * Check the preset's settings fit `table`, as they would be limited when
+     * chosen by hand.
+     * Returns each problem found
+     */
+    fn check(&self, table: &TableConfig) -> Vec<String> {
+        let settings = &self.settings;
+        let mut errors = vec![];
+
+        if !(1..=table.max_decks).contains(&settings.deck_count) {
+            errors.push(format!("deck_count must be 1-{}, not {}", table.max_decks, settings.deck_count));
+        }
+        if !(1..=table.max_hands).contains(&settings.hand_count) {
+            errors.push(format!("hand_count must be 1-{}, not {}", table.max_hands, settings.hand_count));
+        }
+        if let ShoeMode::Continuous { buffer } = settings.shoe && buffer > settings.deck_count * 13 {
+            errors.push(format!("shuffler can hold at most {} cards, not {}", settings.deck_count * 13, buffer));
+        }
+        if let ShuffleStyle::Human(steps) = settings.shuffle {
+            let ranges = [
+                ("riffles", steps.riffles, 1, 9),
+                ("clumping", steps.clumping as usize, 0, 99),
+                ("strip_cuts", steps.strip_cuts, 0, 9),
+                ("penetration", steps.penetration as usize, 10, 95),
+                ("cut_spread", steps.cut_spread, 0, 52),
+            ];
+            for (field, value, low, high) in ranges {
+                if !(low..=high).contains(&value) {
+                    errors.push(format!("shuffle {} must be {}-{}, not {}", field, low, high, value));
+                }
+            }
+        }
+        if let Some(rule) = settings.charlie {
+            if !(5..=7).contains(&rule.cards) {
+                errors.push(format!("charlie cards must be 5-7, not {}", rule.cards));
+            }
+            if rule.num <= 0 || rule.den <= 0 {
+                errors.push(format!("charlie pays must be positive, not {}:{}", rule.num, rule.den));
+            }
+        }
+
+        errors
+    }
+
+    /**
+     * A short description of the table. ex: `Classic, 6 decks, 2 hands`
+     */
+    pub fn summary(&self) -> String {
+        let settings = &self.settings;
+        let mut parts = vec![
+            settings.variant.to_string(),
+            format!("{} decks", settings.deck_count),
+            format!("{} hands", settings.hand_count),
+        ];
+        if let Some(rule) = settings.charlie {
+            parts.push(rule.to_string());
+        }
+        parts.extend(settings.side_bets.iter().map(|rule| rule.kind.to_string()));
+        parts.join(", ")
+    }
+}
+
+/**
+ * Find the config file: `$XDG_CONFIG_HOME/blackjack/config.toml`, or under
+ * `~/.config` when that is not set
+ */
+pub fn config_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join(CONFIG_FILE))
+}
+
+/**
+ * Load the config file, if it exists, and use it. A config with any errors
+ * is not used, so the defaults are kept.
+ * Returns the errors found
+ */
+pub fn load_config() -> Vec<String> {
+    let Some(path) = config_path() else {
+        return vec![];
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return vec![],
+        Err(err) => return vec![format!("{}: {}", path.display(), err)],
+    };
+
+    let mut config: Config = match toml::from_str(&text) {
+        Ok(config) => config,
+        Err(err)   => return vec![format!("{}: {}", path.display(), err.to_string().trim_end())],
+    };
+    let errors = config.validate();
+    if !errors.is_empty() {
+        return errors.into_iter().map(|err| format!("{}: {}", path.display(), err)).collect();
+    }
+
+    set_config(config);
+    vec![]
+}
+
+/**
+ * Use `config` for every default
+ */
+pub fn set_config(config: Config) {
+    *CONFIG.lock().unwrap() = Some(config);
+}
+
+/**
+ * Run `f` with the config in use
+ */
+pub fn with_config<T>(f: impl FnOnce(&Config) -> T) -> T {
+    let mut config = CONFIG.lock().unwrap();
+    f(config.get_or_insert_with(Config::default))
+}
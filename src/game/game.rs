@@ -1,31 +1,133 @@
-use prompted::input;
 
-use crate::{cards::{deck::Deck, hand::Hand}, util::{input::{validated_input}, util::format_vec_string}};
+use std::thread;
 
-use super::settings::GameSettings;
+use serde::{Deserialize, Serialize};
+
+use crate::{cards::{card::Card, deck::Deck, hand::{Hand, PontoonRank, SplitRule}, scenario::ScenarioError, suit::Suit, }, util::{input::{read_one_char, validated_input}, keys::{action_for, hint, key_label, Action, Context}, term::{screen_reader, tinput, tprint, tprintln}, util::format_vec_string}};
+
+use super::{chips::SeatBet, money::Money, settings::{CharlieRule, DealSpeed, GameSettings}, sidebets::{SideBetResult, SideBetRule}, player::{Player, Turn}, strategy::{basic_strategy, Strategy}, table::{SeatView, TableView}, variant::Variant};
 
 pub struct Game {
     deck: Deck,
     hands: Vec<Hand>,
     dealer: Hand,
+    side_bets: Vec<SideBetRule>,
+    variant: Variant,
+    charlie: Option<CharlieRule>,
+    split_rule: SplitRule,
+    speed: DealSpeed,
+    hole_revealed: bool,    // whether the dealer has turned over their cards
+    player: Option<Box<dyn Player>>, // plays every hand instead of the keyboard
+    owners: Vec<usize>,     // the wallet paying for each seat this round
+    practice: bool,         // plays may be taken back, and what ifs shown
+}
+
+/**
+ * The table as it stood before a play, so the play can be taken back
+ */
+struct Snapshot {
+    deck: Deck,
+    hands: Vec<Hand>,
+    available: Vec<Money>,
+    hand: usize,            // the hand the play was made on
+}
+
+/**
+ * Makes one chosen play, then plays on by basic strategy. Used to play out
+ * what ifs
+ */
+struct Chosen {
+    action: Option<Action>,
+}
+
+/**
+ * What a play input did to the hand being played
+ */
+enum PlayResult {
+    Continue,   // keep playing the hand
+    EndTurn,    // move to the next hand
+    Doubled,    // bought a double down
+    Split,      // bought a split
+}
+
+/**
+ * How a hand finished against the dealer. Holds the amount paid back
+ */
+enum Outcome {
+    Blackjack(Money),
+    Win(Money),
+    Bonus(Money, &'static str),
+    Push(Money),
+    Surrendered(Money),
+    Charlie(Money),
+    Busted,
+    Lost,
+}
+
+/**
+ * The money side of a finished round, in total and for each seat, with how
+ * every hand finished
+ */
+pub struct RoundResult {
+    pub payout: Money,                  // paid out on main bets
+    pub spent: Money,                   // spent buying hands on split or double
+    pub bought: usize,                  // number of hands bought
+    pub side_bets: Vec<SideBetResult>,  // each side bet that was placed
+    pub seats: Vec<SeatResult>,         // the above for each seat, main bets only
+    pub dealer: Vec<Card>,
+    pub hands: Vec<HandResult>,
+}
+
+/**
+ * The main bet money of one seat in a finished round
+ */
+#[derive(Clone, Copy, Default)]
+pub struct SeatResult {
+    pub payout: Money,
+    pub spent: Money,
+    pub bought: usize,
+}
+
+/**
+ * How one hand finished
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HandResult {
+    pub seat: usize,
+    pub cards: Vec<Card>,
+    pub total: String,      // ex: `soft 19`
+    pub stake: Money,       // including any double
+    pub result: String,     // ex: `win`, `push`, `busted`
+    pub payout: Money,
 }
 
 impl Game {
     /**
      * Create a new game with `GameSettings`. 
-     * Creates the empty hands needed for playing.
+     * Creates the empty hands needed for playing, which may be more than one
+     * per seat depending on game mode.
      * Creates the full deck needed for playing
      */
     pub fn new(settings: &GameSettings) -> Game {
         // create the game
+        let hand_count = settings.hand_count * settings.variant.hands_per_seat();
         let mut g = Game {
-            deck: Deck::new(settings.deck_count),
-            hands: Vec::with_capacity(settings.hand_count),
+            deck: Deck::new(settings.deck_count, settings.variant, settings.shoe, settings.shuffle),
+            hands: Vec::with_capacity(hand_count),
             dealer: Hand::new(),
+            side_bets: settings.side_bets.clone(),
+            variant: settings.variant,
+            charlie: settings.charlie,
+            split_rule: settings.split_rule,
+            speed: settings.speed,
+            hole_revealed: false,
+            player: None,
+            owners: vec![],
+            practice: false,
         };
 
         // add the hands
-        for _ in 0..settings.hand_count {
+        for _ in 0..hand_count {
             g.hands.push(Hand::new());
         }
 
@@ -35,85 +137,246 @@ impl Game {
     /**
      * Modifies this game to use new settings.
      * Removes the old hands and creates new ones if the number has changed.
-     * Removes the old deck and creates a new one if the number has changed
+     * Removes the old deck and creates a new one if the number, game mode,
+     * shoe mode, or shuffle has changed
      */
     pub fn update_settings(&mut self, settings: &GameSettings) {
-        if self.deck.size() != settings.deck_count {
-            self.deck = Deck::new(settings.deck_count);
+        if self.deck.size() != settings.deck_count
+            || self.deck.variant() != settings.variant
+            || self.deck.shoe() != settings.shoe
+            || self.deck.shuffle_style() != settings.shuffle {
+            self.deck = Deck::new(settings.deck_count, settings.variant, settings.shoe, settings.shuffle);
         }
 
-        if self.hands.len() != settings.hand_count {
+        let hand_count = settings.hand_count * settings.variant.hands_per_seat();
+        if self.hands.len() != hand_count {
             self.hands.clear();
-            for _ in 0..settings.hand_count {
+            for _ in 0..hand_count {
                 self.hands.push(Hand::new());
             }
         }
+
+        self.side_bets = settings.side_bets.clone();
+        self.variant = settings.variant;
+        self.charlie = settings.charlie;
+        self.split_rule = settings.split_rule;
+        self.speed = settings.speed;
+    }
+
+    /**
+     * Let `strategy` play every hand, for simulations. Nothing is read from
+     * the player, and cards are never switched
+     */
+    pub fn set_strategy(&mut self, strategy: Strategy) {
+        self.set_player(Box::new(strategy));
+    }
+
+    /**
+     * Let `player` play every hand instead of reading the keyboard. Cards
+     * are never switched
+     */
+    pub fn set_player(&mut self, player: Box<dyn Player>) {
+        self.player = Some(player);
+    }
+
+    /**
+     * Play for practice: while playing a hand, the last play may be taken
+     * back and what each play would have done may be shown
+     */
+    pub fn set_practice(&mut self, practice: bool) {
+        self.practice = practice;
+    }
+
+    /**
+     * Stack the shoe so `cards` are dealt first, in order.
+     * The rest of the shoe is shuffled as normal.
+     * Returns an error if the shoe does not hold the cards
+     */
+    pub fn stack_deck(&mut self, cards: &[Card]) -> Result<(), ScenarioError> {
+        self.deck.stack(cards)
     }
 
     /**
      * Play a game of blackjack with the current settings.
      * hands must be empty for correct functionality.
-     * `spare_hands` is the amount of hands currently purchasable;
+     * `bets` is the amount staked on each seat's hands, and its side bets.
+     * `available` is the amount of money left to spend;
      * this is used for purchasing hands on split or double.
-     * returns the amount of payout and remaining spare hands.
+     * returns the payouts and purchases made during the round.
      * hands are emptied at the end of this function
      */
-    pub fn play(&mut self, spare_hands: usize) -> (f32, usize) {
+    pub fn play(&mut self, bets: &[SeatBet], available: Money) -> RoundResult {
+        self.play_table(bets, &[available], &[])
+    }
+
+    /**
+     * Play a round where seats are paid for from different wallets, as with
+     * several players at one table.
+     * `wallets` is the money each has left to spend, and `owners` is the
+     * wallet of each seat. Seats not listed spend from the first wallet.
+     * Otherwise the same as `play`
+     */
+    pub fn play_table(&mut self, bets: &[SeatBet], wallets: &[Money], owners: &[usize]) -> RoundResult {
         // get the current size of the hand. needed to remove hands if split
         let hand_count = self.hands.len();
+        self.owners = owners.to_vec();
+        let mut available = wallets.to_vec();
+
+        // stake each hand with its seat's bet
+        let per_seat = self.variant.hands_per_seat();
+        for (i, hand) in self.hands.iter_mut().enumerate() {
+            hand.set_bet(bets[i / per_seat].main);
+            hand.set_seat(i / per_seat);
+        }
 
         // deal two cards to each player and the dealer, one at a 
         // time in a circle
         for _ in 0..2 {
-            for hand in self.hands.iter_mut() {
-                hand.draw_from(&mut self.deck);
+            for i in 0..self.hands.len() {
+                let card = self.hands[i].draw_from(&mut self.deck);
+                self.pace(available[0], format!("Hand {} is dealt {}", i + 1, card));
+            }
+            let card = self.dealer.draw_from(&mut self.deck);
+            match self.dealer.cards().len() <= self.dealer_face_up() {
+                true  => self.pace(available[0], format!("Dealer is dealt {}", card)),
+                false => self.pace(available[0], String::from("Dealer is dealt a card face down")),
             }
-            self.dealer.draw_from(&mut self.deck);
         }
+        self.end_pace();
 
-        let wins;
-        let remaining_spares;
-        // switch on if dealer got blackjack.
-        // gets `if blackjack` and `num player blackjacks`
-        (wins, remaining_spares) = match self.dealer_blackjack() {
-            (true, amt) => {
-                (amt as f32, spare_hands)
-            },
-            _ => {
-                // run the player turns, tracking bought hands.
+        // side bets are settled on the first cards, before any play
+        let side_bets = self.settle_side_bets(bets);
+
+        // let each seat switch cards between its hands, if allowed
+        if self.variant.allows_switch() && self.player.is_none() {
+            self.run_switch_phase();
+        }
+
+        // on a dealer blackjack, only player blackjacks are paid
+        let outcomes = match self.dealer_blackjack() {
+            true => self.natural_outcomes(),
+            false => {
+                // run the player turns, tracking money spent on bought hands.
                 // `0` means to start at the first hand
-                let remaining_spares = self.run_player_turns(spare_hands, 0);
+                self.run_player_turns(&mut available, 0);
 
                 // run the dealer's turn then run win detection and feedback
-                self.run_dealer_turn();
-                (self.check_wins() as f32, remaining_spares)
+                self.run_dealer_turn(available[0]);
+                self.check_wins()
             }
         };
 
+        // count bought hands for each seat: one for each split and each
+        // double or buy, unless the house put up the stake
+        let mut seats = vec![SeatResult::default(); bets.len()];
+        for (seat, result) in seats.iter_mut().enumerate() {
+            let hands: Vec<&Hand> = self.hands.iter().filter(|hand| hand.seat() == seat).collect();
+            result.bought = (hands.len() - per_seat)
+                - hands.iter().filter(|hand| hand.is_free_split()).count()
+                + hands.iter().filter(|hand| !hand.is_free_doubled()).map(|hand| hand.buys()).sum::<usize>();
+            result.spent = bets[seat].main * result.bought;
+        }
+
+        // note how each hand finished, paying out to its seat
+        let hands = self.hands.iter().zip(outcomes.iter()).map(|(hand, outcome)| {
+            seats[hand.seat()].payout += outcome.payout();
+            HandResult {
+                seat: hand.seat(),
+                cards: hand.cards().to_vec(),
+                total: hand.total().to_string(),
+                stake: hand.stake(),
+                result: String::from(outcome.name(hand)),
+                payout: outcome.payout(),
+            }
+        }).collect();
+        let dealer = self.dealer.cards().to_vec();
+
         // discard all cards in all hands
         for hand in self.hands.iter_mut() {
             self.deck.discard_hand(hand);
             hand.set_doubled(false);
+            hand.set_surrendered(false);
+            hand.set_split(false);
+            hand.set_free_doubled(false);
+            hand.set_free_split(false);
         }
         self.deck.discard_hand(&mut self.dealer);
+        self.deck.end_round();
+        self.hole_revealed = false;
 
         // remove any hands gained from splitting
         for _ in 0..(self.hands.len() - hand_count) {
             self.hands.pop();
         }
 
-        // return the amount of payout, and the amount of bought hands
-        (wins, spare_hands - remaining_spares)
+        // return the amount of payout, and the amount spent on bought hands
+        RoundResult {
+            payout: seats.iter().map(|seat| seat.payout).sum(),
+            spent: seats.iter().map(|seat| seat.spent).sum(),
+            bought: seats.iter().map(|seat| seat.bought).sum(),
+            side_bets,
+            seats,
+            dealer,
+            hands,
+        }
+    }
+
+    /**
+     * Settle every side bet placed in `bets` against each seat's first two
+     * cards and the dealer's upcard
+     */
+    fn settle_side_bets(&self, bets: &[SeatBet]) -> Vec<SideBetResult> {
+        let upcard = self.dealer.top_card();
+        let mut results = vec![];
+
+        // for each side bet placed on each seat, find its rule and settle
+        // against the seat's first hand
+        let per_seat = self.variant.hands_per_seat();
+        for (seat, bet) in bets.iter().enumerate() {
+            let hand = &self.hands[seat * per_seat];
+            for (kind, stake) in bet.side.iter() {
+                if let Some(rule) = self.side_bets.iter().find(|rule| rule.kind == *kind) {
+                    results.push(rule.settle(seat, *stake, hand.cards(), upcard));
+                }
+            }
+        }
+
+        results
+    }
+
+    /**
+     * Offer each seat the switch of the second cards between its two hands.
+     * Shown seat by seat before any hand is played
+     */
+    fn run_switch_phase(&mut self) {
+        for seat in 0..self.hands.len() / 2 {
+            let (first, second) = (seat * 2, seat * 2 + 1);
+
+            // show both hands as they are, and as they would be if switched
+            let mut switched = (self.hands[first].clone(), self.hands[second].clone());
+            switched.0.swap_card(&mut switched.1, 1);
+            tprint!("[2J\nDealer Hand: {}\n\nSeat {}:\n", self.dealer_shown(), seat + 1);
+            tprintln!("Keep:   Hand {}: {} ; ({})  Hand {}: {} ; ({})",
+                first + 1, self.hands[first], self.hands[first].total(),
+                second + 1, self.hands[second], self.hands[second].total());
+            tprintln!("Switch: Hand {}: {} ; ({})  Hand {}: {} ; ({})",
+                first + 1, switched.0, switched.0.total(),
+                second + 1, switched.1, switched.1.total());
+            tprint!("\nSwitch second cards? {} switches, any other key keeps\n:: ", key_label(Action::SwitchCards));
+
+            // only switch on the switch key
+            if action_for(Context::Play, read_one_char()) == Some(Action::SwitchCards) {
+                self.hands[first] = switched.0;
+                self.hands[second] = switched.1;
+            }
+        }
     }
 
     /**
      * Check for a dealer blackjack, and if present, for player blackjacks.
-     * Returns:
-     *      if dealer got blackjack
-     *      if above is true, number of hands player got blackjack on.
-     *          if false, 0.
+     * Returns if dealer got blackjack
      */
-    fn dealer_blackjack(&self) -> (bool, usize) {
+    fn dealer_blackjack(&self) -> bool {
         let mut blackjacks = Vec::new();
 
         // check for dealer blackjack
@@ -128,172 +391,589 @@ impl Game {
             }
         }
 
-        // output
-        println!("\n[2JDealer: {}, {}", self.dealer, format_vec_string(&self.dealer.filter_value()));
-        for (i, hand) in self.hands.iter().enumerate() {
-            println!("{}: {}, {}", i + 1, hand, format_vec_string(&hand.filter_value()));
-        }
-
         // check counts
         let len = blackjacks.len();
         let dealer_jack = blackjacks.contains(&0);
+
+        // output. the dealer's cards are only revealed on a blackjack
+        if dealer_jack {
+            tprintln!("\n[2J{}", describe_hand("Dealer", &self.dealer));
+            for (i, hand) in self.hands.iter().enumerate() {
+                tprintln!("{}", describe_hand(&format!("Hand {}", i + 1), hand));
+            }
+        }
+
+        let natural = self.variant.natural_name();
         match (dealer_jack, len) {
             (true, 1) => { // only dealer blackjack
-                println!("Dealer got blackjack. Player loses all hands.");
-                (true, 0)
+                tprintln!("Dealer got {}. Player loses all hands.", natural);
+                true
             },
 
             (true, _) => { // both dealer and player blackjack
                 blackjacks.remove(0);
+                let result = match self.variant {
+                    Variant::Classic | Variant::Switch | Variant::FreeBet => "regains bet",
+                    Variant::Spanish21 => "wins 3:2",
+                    Variant::DoubleExposure => "wins 1:1",
+                    Variant::Pontoon => "loses",
+                };
                 if len == 2 {
-                    println!("Both Player and Dealer got blackjack. Player regains bet for hand {}.", format_vec_string(&blackjacks));
+                    tprintln!("Both Player and Dealer got {}. Player {} for hand {}.", natural, result, format_vec_string(&blackjacks));
                 } else {
-                    println!("Both Player and Dealer got blackjack. Player regains bet for hands {}.", format_vec_string(&blackjacks));
+                    tprintln!("Both Player and Dealer got {}. Player {} for hands {}.", natural, result, format_vec_string(&blackjacks));
                 }
-
-                (true, len - 1)
+                true
             },
 
-            (_, _) => (false, 0),
+            (_, _) => false,
         }
     }
 
     /**
-     * Run the player gameplay loop for the turn.
-     * Takes number of buyable hands and starting hand index.
-     * Returns number of remaining buyable hands
-     */
-    fn run_player_turns(&mut self, mut spare_hands: usize, from: usize) -> usize {
-        let mut last_input = 'x';
-        let mut cl = self.hands.clone();
-        let iter = cl.iter_mut().enumerate().skip(from);
-
-
-        // for each hand
-        for (n, mut hand) in iter {
-            // keep going until turn ends
-            loop {
-                // show dealer hand
-                print!("[2J\nDealer Hand: {}, ??\n\nOptions: ", self.dealer.top_card());
-
-                // check split and buy status
-                let splittable = hand.is_splittable();
-                let buyable = spare_hands > 0;
-
-                // get and display hand play options
-                let options = match (splittable, buyable) {
-                    (true, true) => {
-                        println!("[H]it, [S]tand, [D]ouble, sp[L]it");
-                        vec!['h', 's', 'd', 'l', 'H', 'S', 'D', 'L']
-                    },
-                    (false, true) => {
-                        println!("[H]it, [S]tand, [D]ouble");
-                        vec!['h', 's', 'd', 'H', 'S', 'D']
-                    },
-                    (_, false) => {
-                        println!("[H]it, [S]tand");
-                        vec!['h', 's', 'H', 'S']
-                    },
-                };
+     * Settle each hand against a dealer blackjack. Player blackjacks are paid
+     * by game mode, every other hand loses
+     */
+    fn natural_outcomes(&self) -> Vec<Outcome> {
+        self.hands.iter().map(|hand| match (hand.is_blackjack(), self.blackjack_against_dealer(hand.bet())) {
+            (false, _) => Outcome::Lost,
+            (true, paid) if paid.is_zero() => Outcome::Lost,
+            (true, paid) if paid == hand.bet() => Outcome::Push(paid),
+            (true, paid) => Outcome::Blackjack(paid),
+        }).collect()
+    }
 
-                // display all hands, revealing that which has been played
-                let mut out = String::new();
-                for (i, hand) in self.hands.iter().enumerate() {
-                    match (i < n, i == n) {
-                        (true , false) => {
-                            match hand.is_busted() {
-                                true  => println!("Hand {}: {} ; ({}, busted)", i + 1, hand, format_vec_string(&hand.value())),
-                                false => println!("Hand {}: {} ; ({})", i + 1, hand, format_vec_string(&hand.filter_value())),
-                            }
-                        },
-                        (false, true) => {
-                            out = format!("Hand {}: {} ; ({}) <- ", i + 1, hand, format_vec_string(&hand.filter_value()));
-                            println!()
-                        },
-                        (_    , _    ) => println!("Hand {}: {}, ??", i + 1, hand.top_card()),
-                    }
+    /**
+     * Get the dealer's hand as shown during play. The hole card is hidden
+     * unless the game mode deals it face up
+     */
+    fn dealer_shown(&self) -> String {
+        match (self.variant.shows_hole_card(), self.variant.hides_dealer_hand()) {
+            (true, _)      => format!("{} ; ({})", self.dealer, self.dealer.total()),
+            (false, true)  => self.dealer.display_hidden(0),
+            (false, false) => self.dealer.display_hidden(1),
+        }
+    }
+
+    /**
+     * The number of dealer cards shown face up. The hole card is hidden
+     * until the dealer turns it over, unless the game mode deals it face up
+     */
+    fn dealer_face_up(&self) -> usize {
+        let dealt = self.dealer.cards().len();
+        match (self.hole_revealed || self.variant.shows_hole_card(), self.variant.hides_dealer_hand()) {
+            (true, _)      => dealt,
+            (false, true)  => 0,
+            (false, false) => dealt.min(1),
+        }
+    }
+
+    /**
+     * Show one step of the deal when dealing is paced, then pause.
+     * The table shows `event` in its action bar; screen readers hear it
+     */
+    fn pace(&self, available: Money, event: String) {
+        if !self.speed.is_paced() {
+            return;
+        }
+
+        match screen_reader() {
+            true  => tprintln!("{}.", event),
+            false => self.table_view(None, available, event).show(),
+        }
+        thread::sleep(self.speed.delay());
+    }
+
+    /**
+     * Take the paced table off screen once a run of steps is done
+     */
+    fn end_pace(&self) {
+        if self.speed.is_paced() && !screen_reader() {
+            TableView::close();
+        }
+    }
+
+    /**
+     * Build the table as it stands, with hand `active` being played.
+     * Hands after it show only their first card.
+     * `available` is the money left to spend, and `actions` fills the action
+     * bar
+     */
+    fn table_view(&self, active: Option<usize>, available: Money, actions: String) -> TableView {
+        // the dealer's cards as shown during play
+        let dealer_cards = self.dealer.cards().to_vec();
+        let shown = self.dealer_face_up();
+        let note = match shown == dealer_cards.len() && shown > 0 {
+            true  => self.dealer.total().to_string(),
+            false => String::new(),
+        };
+        let dealer = SeatView {
+            label: String::from("Dealer"),
+            hidden: dealer_cards.len() - shown,
+            cards: dealer_cards[..shown].to_vec(),
+            note,
+            active: false,
+        };
+
+        // each hand, with its stake and how it was played
+        let seats = self.hands.iter().enumerate().map(|(i, hand)| {
+            let unplayed = active.is_some_and(|n| i > n);
+            let shown = match unplayed {
+                true  => hand.cards().len().min(1),
+                false => hand.cards().len(),
+            };
+            let mut label = format!("Hand {}  {}", i + 1, hand.bet());
+            if hand.is_doubled() {
+                label.push_str(&format!(" x{}", 1 + hand.buys()));
+            }
+            SeatView {
+                label,
+                cards: hand.cards()[..shown].to_vec(),
+                hidden: hand.cards().len() - shown,
+                note: match (unplayed || hand.cards().is_empty(), hand.is_surrendered()) {
+                    (true, _)      => String::new(),
+                    (false, true)  => format!("{}, surrendered", hand.total()),
+                    (false, false) => hand.total().to_string(),
+                },
+                active: active == Some(i),
+            }
+        }).collect();
+
+        // money and shoe at a glance
+        let staked: Money = self.hands.iter().map(|hand| hand.stake()).sum();
+        let status = format!(" Balance: {}   Bet: {}   Shoe: {} cards left", available, staked, self.deck.remaining());
+
+        TableView { status, dealer, seats, actions }
+    }
+
+    /**
+     * Show hand `n` for play, and read what to do with it.
+     * The table is drawn full screen, or as plain lines for screen readers.
+     * Enter alone repeats `last_input` if it is still an option.
+     * Returns `None` if no option was picked
+     */
+    fn read_play_input(&self, n: usize, available: Money, labels: &[String], options: &[Action], last_input: Option<Action>) -> Option<Action> {
+        // keys bound to an offered option, then enter to repeat
+        let offered = |c: char| action_for(Context::Play, c).filter(|action| options.contains(action));
+        let repeat = last_input.filter(|action| options.contains(action));
+
+        if screen_reader() {
+            tprintln!("\nDealer Hand: {}\n\nOptions: {}", self.dealer_shown(), labels.join(", "));
+            for (i, hand) in self.hands.iter().enumerate() {
+                match i <= n {
+                    true  => tprintln!("{}", describe_hand(&format!("Hand {}", i + 1), hand)),
+                    false => tprintln!("Hand {}: {}", i + 1, hand.display_hidden(1)),
                 }
+            }
+            tprint!("Playing hand {}\n:: ", n + 1);
 
-                // move cursor to correct hand pos
-                let v_shift = self.hands.len() - n;
-                print!("[{}A{out}", v_shift);
-
-                // handle input
-                let (to_break, bought) = match validated_input(|c| options.contains(&c), |s: String| s.len() <= 1) {
-                    input if input.len() == 0 => {
-                        self.handle_play_input(&mut hand, last_input)
-                    },
-                    input => {
-                        last_input = input.chars().nth(0).unwrap();
-                        self.handle_play_input(&mut hand, last_input)
-                    },
-                };
-                self.hands[n] = hand.clone();
-
-                match (to_break, bought) {
-                    (true, true) => {
-                        spare_hands -= 1;
-                        break
-                    },
-                    (true, false) => break,
-                    (false, true) => {
-                        spare_hands -= 1;
-                        return self.split_hand(spare_hands, n)
-                    },
-                    (false, false) => (),
+            return match validated_input(|c| offered(c).is_some(), |s: String| s.len() <= 1) {
+                input if input.is_empty() => repeat,
+                input => input.chars().next().and_then(offered),
+            };
+        }
+
+        // single keys act right away on the full screen table
+        self.table_view(Some(n), available, format!("Hand {}: {}   (enter repeats)", n + 1, labels.join(", "))).show();
+        match read_one_char() {
+            c if offered(c).is_some() => offered(c),
+            '\n' => repeat,
+            _ => None,
+        }
+    }
+
+    /**
+     * Run the player gameplay loop for the turn.
+     * Takes the money in each wallet for buying hands, which is spent from,
+     * and the starting hand index.
+     * Practice tables also offer taking back the last play, which puts the
+     * hands, shoe, and wallets back as they were, and showing what ifs
+     */
+    fn run_player_turns(&mut self, available: &mut [Money], from: usize) {
+        let mut last_input = None;
+        let mut history: Vec<Snapshot> = vec![];
+        let mut n = from;
+
+        // play each hand until its turn ends. hands split off are played
+        // right after the hand they came from
+        loop {
+            // show state before moving on. the last play may still be
+            // taken back in practice
+            if n >= self.hands.len() {
+                if self.player.is_some() || !self.end_player_turns(available[0], !history.is_empty()) {
+                    return;
                 }
-                if bought {
-                    spare_hands -= 1;
+                if let Some(snapshot) = history.pop() {
+                    n = self.restore(snapshot, available);
                 }
+                continue;
+            }
+
+            // get the hand play options
+            // with hints from the key bindings
+            let mut hand = self.hands[n].clone();
+            let wallet = self.wallet(&hand);
+            let offered = self.play_options(&hand, available[wallet]);
+            let mut labels: Vec<String> = offered.iter().map(|(label, action)| hint(label, *action)).collect();
+            let mut options: Vec<Action> = offered.iter().map(|(_, action)| *action).collect();
+            if self.practice && self.player.is_none() {
+                if !history.is_empty() {
+                    labels.push(hint("undo", Action::Undo));
+                    options.push(Action::Undo);
+                }
+                labels.push(hint("what if", Action::WhatIf));
+                options.push(Action::WhatIf);
+            }
+
+            // show the table and handle input, or let the player play
+            let action = match self.player.take() {
+                Some(mut player) => {
+                    let action = self.ask_player(player.as_mut(), n, &hand, &offered, available[wallet]);
+                    self.player = Some(player);
+                    Some(action)
+                },
+                None => self.read_play_input(n, available[wallet], &labels, &options, last_input),
+            };
+            let action = match action {
+                Some(Action::Undo) => {
+                    if let Some(snapshot) = history.pop() {
+                        n = self.restore(snapshot, available);
+                    }
+                    last_input = None;
+                    continue;
+                },
+                Some(Action::WhatIf) => {
+                    self.show_what_ifs(n, &offered, available);
+                    continue;
+                },
+                Some(action) => action,
+                None => continue,
+            };
+
+            // keep the table as it was, to take the play back
+            if self.practice {
+                history.push(Snapshot {
+                    deck: self.deck.clone(),
+                    hands: self.hands.clone(),
+                    available: available.to_vec(),
+                    hand: n,
+                });
+            }
+            last_input = Some(action);
+            let result = self.handle_play_input(&mut hand, action);
+            self.hands[n] = hand.clone();
+
+            match result {
+                PlayResult::Continue => (),
+                PlayResult::EndTurn  => {
+                    n += 1;
+                    continue;
+                },
+                PlayResult::Doubled  => {
+                    if !hand.is_free_doubled() {
+                        available[wallet] -= hand.bet();
+                    }
+
+                    // a doubled hand may still be rescued, or keep
+                    // twisting after a buy, if allowed
+                    if !self.variant.allows_rescue() && !self.variant.is_pontoon() {
+                        n += 1;
+                        continue;
+                    }
+                },
+                PlayResult::Split    => {
+                    let free = self.is_free_split(&hand);
+                    if !free {
+                        available[wallet] -= hand.bet();
+                    }
+                    self.split_hand(n, free);
+                    continue;
+                },
+            }
+
+            // pontoon hands stop at a five-card trick, and charlies win
+            // right away
+            if hand.is_busted() || (self.variant.is_pontoon() && hand.cards().len() >= 5) || self.is_charlie(&hand) {
+                n += 1;
+            }
+        }
+    }
+
+    /**
+     * Show every hand once all are played, and wait to move to the dealer's
+     * turn. `can_undo` offers taking back the last play instead.
+     * Returns whether to take back the last play
+     */
+    fn end_player_turns(&self, available: Money, can_undo: bool) -> bool {
+        let prompt = match can_undo {
+            true  => format!("Moving to dealer's turn. Enter to continue, {} to undo...", key_label(Action::Undo)),
+            false => String::from("Moving to dealer's turn. Enter to continue..."),
+        };
+        let undoes = |c: char| can_undo && action_for(Context::Play, c) == Some(Action::Undo);
+
+        if screen_reader() {
+            tprintln!("\nDealer Hand: {}\n", self.dealer_shown());
+            for (i, hand) in self.hands.iter().enumerate() {
+                tprintln!("{}", describe_hand(&format!("Hand {}", i + 1), hand));
+            }
+            return tinput!("{}", prompt).trim().chars().next().is_some_and(undoes);
+        }
+
+        self.table_view(None, available, prompt).show();
+        loop {
+            match read_one_char() {
+                '\n' => break,
+                c if undoes(c) => return true,
+                _ => (),
+            }
+        }
+        TableView::close();
+        false
+    }
+
+    /**
+     * Put the hands, shoe, and wallets back as they were in `snapshot`.
+     * Returns the hand to play from
+     */
+    fn restore(&mut self, snapshot: Snapshot, available: &mut [Money]) -> usize {
+        self.deck = snapshot.deck;
+        self.hands = snapshot.hands;
+        available.copy_from_slice(&snapshot.available);
+        snapshot.hand
+    }
+
+    /**
+     * Show how hand `n` would finish with each of the `offered` plays,
+     * against the cards really left in the shoe.
+     * Each is played out on a copy of the table: later plays on the hand
+     * and the hands after it go by basic strategy, then the dealer plays
+     */
+    fn show_what_ifs(&self, n: usize, offered: &[(&'static str, Action)], available: &[Money]) {
+        let mut lines = vec![];
+        for (label, action) in offered {
+            let mut game = self.copy_table();
+            let mut available = available.to_vec();
+            game.set_player(Box::new(Chosen { action: Some(*action) }));
 
-                if hand.is_busted() {
-                    break;
+            // play the hand and any split from it, then the hands after it
+            let later = game.hands.split_off(n + 1);
+            game.run_player_turns(&mut available, n);
+            let played = game.hands.len();
+            game.hands.extend(later);
+            game.run_player_turns(&mut available, played);
+            game.run_dealer_turn(available[0]);
+
+            // what the hand won or lost, less what was staked on it
+            let mut net = Money::ZERO;
+            let mut hands = vec![];
+            for hand in game.hands[n..played].iter() {
+                let outcome = game.settle_hand(hand);
+                net += outcome.payout();
+                if !hand.is_free_split() {
+                    net -= hand.bet();
+                }
+                if !hand.is_free_doubled() {
+                    net -= hand.stake() - hand.bet();
                 }
+                hands.push(format!("{} ; ({}) {}", hand, hand.total(), outcome.name(hand)));
             }
+            let net = match net > Money::ZERO {
+                true  => format!("+{}", net),
+                false => net.to_string(),
+            };
+            lines.push(format!("{}: {}. Dealer: {} ; ({}). {}", label, hands.join(", "), game.dealer, game.dealer.total(), net));
         }
 
-        // show state before moving on
-        print!("[2J\nDealer Hand: {}, ??\n\n\n", self.dealer.top_card());
-        for (i, hand) in self.hands.iter().enumerate() {
-            match hand.is_busted() {
-                true  => println!("Hand {}: {} ; ({}, busted)", i + 1, hand, format_vec_string(&hand.value())),
-                false => println!("Hand {}: {} ; ({})", i + 1, hand, format_vec_string(&hand.filter_value())),
+        // list them off the table, then go back to it
+        if !screen_reader() {
+            TableView::close();
+        }
+        tprintln!("What if, for hand {} with the cards left in the shoe:\n", n + 1);
+        for line in lines {
+            tprintln!("{}", line);
+        }
+        tinput!("\nLater plays go by basic strategy. Enter to go back...");
+    }
+
+    /**
+     * Copy the table as it stands, to play on without changing it. The copy
+     * deals instantly and has no one to play its hands
+     */
+    fn copy_table(&self) -> Game {
+        Game {
+            deck: self.deck.clone(),
+            hands: self.hands.clone(),
+            dealer: self.dealer.clone(),
+            side_bets: vec![],
+            variant: self.variant,
+            charlie: self.charlie,
+            split_rule: self.split_rule,
+            speed: DealSpeed::Instant,
+            hole_revealed: self.hole_revealed,
+            player: None,
+            owners: self.owners.clone(),
+            practice: false,
+        }
+    }
+
+    /**
+     * Get the wallet that pays for `hand`, by the owner of its seat
+     */
+    fn wallet(&self, hand: &Hand) -> usize {
+        self.owners.get(hand.seat()).copied().unwrap_or(0)
+    }
+
+    /**
+     * Ask `player` how to play hand `n`, from the `options` offered.
+     * A play that was not offered falls back to standing
+     */
+    fn ask_player(&self, player: &mut dyn Player, n: usize, hand: &Hand, options: &[(&'static str, Action)], available: Money) -> Action {
+        // hands are played against the face up card only
+        let upcard = match self.dealer_face_up() {
+            0 => None,
+            _ => Some(self.dealer.top_card()),
+        };
+        let turn = Turn {
+            index: n,
+            owner: self.wallet(hand),
+            hand,
+            hands: &self.hands,
+            upcard,
+            dealer: &self.dealer.cards()[..self.dealer_face_up()],
+            charlie: self.charlie,
+            options,
+            available,
+            table: self.table_view(Some(n), available, String::new()),
+        };
+
+        match player.play(&turn) {
+            action if turn.offers(action) => action,
+            _ => turn.fallback(),
+        }
+    }
+
+    /**
+     * Get the play options for a hand, as a label and its action.
+     * Takes the money available for buying hands
+     */
+    fn play_options(&self, hand: &Hand, available: Money) -> Vec<(&'static str, Action)> {
+        if self.variant.is_pontoon() {
+            return self.pontoon_options(hand, available);
+        }
+
+        // a doubled hand still being played can only stand or be rescued
+        if hand.is_doubled() {
+            return vec![("stand", Action::Stand), ("rescue", Action::Surrender)];
+        }
+
+        // check split, buy, and surrender status
+        let splittable = hand.is_splittable(self.split_rule);
+        let buyable = available >= hand.bet();
+        let surrenderable = self.variant.allows_surrender() && hand.cards().len() == 2;
+
+        // free doubles and splits need no money
+        let mut options = vec![("hit", Action::Hit), ("stand", Action::Stand)];
+        if self.is_free_double(hand) {
+            options.push(("double (free)", Action::Double));
+        } else if buyable {
+            options.push(("double", Action::Double));
+        }
+        if self.is_free_split(hand) {
+            options.push(("split (free)", Action::Split));
+        } else if buyable && splittable {
+            options.push(("split", Action::Split));
+        }
+        if surrenderable {
+            options.push(("surrender", Action::Surrender));
+        }
+
+        options
+    }
+
+    /**
+     * Get the Pontoon play options for a hand. A hand must twist below the
+     * minimum to stick on, and may keep buying cards for its bet again until
+     * the five-card trick. Only a hand not yet bought on may split
+     */
+    fn pontoon_options(&self, hand: &Hand, available: Money) -> Vec<(&'static str, Action)> {
+        let mut options = vec![("twist", Action::Hit)];
+        if hand.true_value() >= self.variant.min_stand() {
+            options.push(("stick", Action::Stand));
+        }
+        if hand.cards().len() < 5 && available >= hand.bet() {
+            options.push(("buy", Action::Double));
+            if !hand.is_doubled() && hand.is_splittable(self.split_rule) {
+                options.push(("split", Action::Split));
             }
         }
-        input!("Moving to dealer's turn. Enter to continue...");
 
-        spare_hands
+        options
     }
 
     /**
      * Handle input during play
      */
-    fn handle_play_input(&mut self, hand: &mut Hand, c: char) -> (bool, bool) {
-        match c {
-            'h' | 'H' => {
+    fn handle_play_input(&mut self, hand: &mut Hand, action: Action) -> PlayResult {
+        match action {
+            // hit, or twist in pontoon
+            Action::Hit => {
                 hand.draw_from(&mut self.deck);
-                (false, false)
+                PlayResult::Continue
             },
-            's' | 'S' => (true, false),
-            'd' | 'D' => {
+            Action::Stand => PlayResult::EndTurn,
+            // double down, or buy in pontoon
+            Action::Double => {
+                // check for a free double before the card changes the total
+                hand.set_free_doubled(self.is_free_double(hand));
                 hand.draw_from(&mut self.deck);
-                hand.set_doubled(true);
-                (true, true)
+                hand.buy();
+                PlayResult::Doubled
+            },
+            Action::Split => PlayResult::Split,
+            // surrender, or rescue a doubled hand
+            Action::Surrender => {
+                hand.set_surrendered(true);
+                PlayResult::EndTurn
             },
-            'l' | 'L' => (false, true),
-            _ => (false, false),
+            _ => PlayResult::Continue,
         }
     }
 
     /**
-     * Handle splitting a hand.
-     * Meant to be used from run_player_turns by returning this function.
+     * Returns whether doubling the hand is free. Free Bet doubles two card
+     * hard 9, 10, and 11 for free
      */
-    fn split_hand(&mut self, spare_hands: usize, ndx: usize) -> usize {
-        // get the current hand. create a new hand, take one card from current
-        // and give to new hand.
+    fn is_free_double(&self, hand: &Hand) -> bool {
+        let total = hand.total();
+        self.variant.offers_free_bets()
+            && total.cards == 2
+            && !total.soft
+            && (9..=11).contains(&total.best)
+    }
+
+    /**
+     * Returns whether splitting the hand is free. Free Bet splits every pair
+     * but tens for free
+     */
+    fn is_free_split(&self, hand: &Hand) -> bool {
+        self.variant.offers_free_bets() && hand.is_splittable(self.split_rule) && !hand.top_card().is_ten_value()
+    }
+
+    /**
+     * Handle splitting a hand. `free` is whether the house puts up the bet of
+     * the new hand.
+     * The new hand is placed right after the hand split, to be played next
+     */
+    fn split_hand(&mut self, ndx: usize, free: bool) {
+        // get the current hand. create a new hand with the same bet, take one
+        // card from current and give to new hand.
         let prev_hand = self.hands.get_mut(ndx).unwrap();
         let mut new_hand = Hand::new();
+        new_hand.set_bet(prev_hand.bet());
+        new_hand.set_seat(prev_hand.seat());
+        new_hand.set_split(true);
+        new_hand.set_free_split(free);
+        prev_hand.set_split(true);
         new_hand.give_card(prev_hand.take_card());
 
         // each hand draws 1 card
@@ -302,77 +982,530 @@ impl Game {
 
         // add the hand, continue playing from current hand
         self.hands.insert(ndx + 1, new_hand);
-        self.run_player_turns(spare_hands, ndx)
     }
 
     /**
      * Runs the dealer's turn.
-     * Plays by hitting until >=17
+     * Plays by hitting until >=17.
+     * When dealing is paced, the hole card is turned over and each hit is
+     * shown. `available` is the money left, for the status bar
      */
-    fn run_dealer_turn(&mut self) {
+    fn run_dealer_turn(&mut self, available: Money) {
+        // turn over the face down cards
+        let face_down: Vec<String> = self.dealer.cards()[self.dealer_face_up()..].iter().map(|card| card.to_string()).collect();
+        self.hole_revealed = true;
+        if !face_down.is_empty() {
+            self.pace(available, format!("Dealer turns over {}", face_down.join(", ")));
+        }
+
         // calc value once per iteration.
         // value is only 0 if busted
+        // pontoon dealers stop at a five-card trick
+        let max_cards = match self.variant.is_pontoon() {
+            true  => 5,
+            false => usize::MAX,
+        };
         let mut value = self.dealer.true_value();
-        while value < 17 && value != 0 {
-            self.dealer.draw_from(&mut self.deck);
+        while value < 17 && value != 0 && self.dealer.cards().len() < max_cards {
+            let card = self.dealer.draw_from(&mut self.deck);
+            self.pace(available, format!("Dealer draws {}", card));
             value = self.dealer.true_value();
         }
+        self.end_pace();
     }
 
     /**
-     * Check win count and calculate payout scalar.
+     * Check win count and settle each hand against the dealer.
+     * Returns how each hand finished, with its payout
      */
-    fn check_wins(&self) -> f32 {
-        print!("[1E[2J");
+    fn check_wins(&self) -> Vec<Outcome> {
+        tprint!("[1E[2J");
 
-        // get the dealer's hand value, get hands that beat the dealer
+        // settle each hand against the dealer by game mode
         let dealer_max = self.dealer.true_value();
-        let winning_hands: Vec<&Hand> = 
-            self.hands.iter()
-            .filter(|hand| hand.true_value() > dealer_max || hand.is_blackjack())
-            .collect();
-        let winning_ndxs: Vec<usize> = 
-            self.hands.iter()
-            .enumerate()
-            .filter(|enume| enume.1.true_value() > dealer_max || enume.1.is_blackjack())
-            .map(|enume| enume.0)
-            .collect();
+        let outcomes: Vec<Outcome> = self.hands.iter().map(|hand| self.settle_hand(hand)).collect();
+        let winning_hands = outcomes.iter().filter(|outcome| outcome.is_win()).count();
 
         // display corresponding header
-        if self.dealer.is_busted() {
-            println!("Dealer: {} ; ({}) [38;5;196m[Busted][0m\n", self.dealer, format_vec_string(&self.dealer.value()));
-            println!("Dealer busted. All non-busted hands win:");
-        } else if winning_hands.is_empty() {
-            println!("Dealer: {} ; ({})\n", self.dealer, self.dealer.true_value());
-            println!("Dealer scored {}, you lost on all hands.", dealer_max);
+        if self.dealer_pushes_22() {
+            tprintln!("{} [38;5;214m[22][0m\n", describe_hand("Dealer", &self.dealer));
+            tprintln!("Dealer got 22. All non-busted hands push, blackjacks win:");
+        } else if self.dealer.is_busted() {
+            tprintln!("{} [38;5;196m[Busted][0m\n", describe_hand("Dealer", &self.dealer));
+            tprintln!("Dealer busted. All non-busted hands win:");
+        } else if winning_hands == 0 {
+            tprintln!("{}\n", describe_hand("Dealer", &self.dealer));
+            tprintln!("Dealer scored {}, you lost on all hands.", dealer_max);
         } else {
-            println!("Dealer: {} ; ({})\n", self.dealer, self.dealer.true_value());
-            println!("Dealer scored {}, you won on {} hands:", dealer_max, winning_hands.len());
-        }
-
-        // take each hand that beat the dealer and convert it to its win amount
-        let payouts: Vec<f32> = winning_hands.iter().map(|hand| {
-            if hand.is_blackjack() { // blackjacks get 1.5x bet
-                1.5
-            } else if hand.is_doubled() { // doubles get 4x bet
-                4.0
-            } else { // standard hands get 2x bet
-                2.0
-            }
-        }).collect();
+            tprintln!("{}\n", describe_hand("Dealer", &self.dealer));
+            tprintln!("Dealer scored {}, you won on {} hands:", dealer_max, winning_hands);
+        }
 
-        // print all winning hands
-        for (i, hand) in self.hands.iter().enumerate() {
-            match (winning_ndxs.contains(&i), hand.is_blackjack(), hand.is_doubled(), hand.is_busted()) {
-                (true, true, false, false)  => println!("Hand {}: {} ; ({}) [38;5;220m[Blackjack][0m", i + 1, hand, hand.true_value()),
-                (true, false, true, false)  => println!("Hand {}: {} ; ({}) [38;5;40m[Win][38;5;220m[x2][0m", i + 1, hand, hand.true_value()),
-                (true, false, false, false) => println!("Hand {}: {} ; ({}) [38;5;40m[Win][0m", i + 1, hand, hand.true_value()),
-                (false, false, false, true) => println!("Hand {}: {} ; ({}) [38;5;196m[Busted][0m", i + 1, hand, format_vec_string(&hand.value())),
-                (_, _, _, _)                => println!("Hand {}: {} ; ({}) [38;5;196m[Lost][0m", i + 1, hand, format_vec_string(&hand.value())),
+        // print all hands with their result tag
+        for (i, (hand, outcome)) in self.hands.iter().zip(outcomes.iter()).enumerate() {
+            tprintln!("{} {}", describe_hand(&format!("Hand {}", i + 1), hand), outcome.tag(hand));
+        }
+
+        outcomes
+    }
+
+    /**
+     * Returns whether the hand reached the Charlie rule's card count without
+     * busting
+     */
+    fn is_charlie(&self, hand: &Hand) -> bool {
+        match self.charlie {
+            Some(rule) => !hand.is_busted() && !hand.is_surrendered() && hand.cards().len() >= rule.cards,
+            None       => false,
+        }
+    }
+
+    /**
+     * Settle a hand against the dealer using the rules of the game mode.
+     * A Charlie wins before the game mode is considered
+     */
+    fn settle_hand(&self, hand: &Hand) -> Outcome {
+        if let (Some(rule), true) = (self.charlie, self.is_charlie(hand)) {
+            // the player's part of the stake back, plus the odds on the
+            // whole stake
+            let stake = hand.stake();
+            return Outcome::Charlie(stake - hand.free_stake() + stake.scale(rule.num, rule.den));
+        }
+
+        match self.variant {
+            Variant::Classic => self.settle_classic(hand),
+            Variant::Spanish21 => self.settle_spanish21(hand),
+            Variant::Switch => self.settle_switch(hand),
+            Variant::DoubleExposure => self.settle_double_exposure(hand),
+            Variant::FreeBet => self.settle_free_bet(hand),
+            Variant::Pontoon => self.settle_pontoon(hand),
+        }
+    }
+
+    /**
+     * Returns whether the dealer busted with exactly 22 in a game mode where
+     * that pushes
+     */
+    fn dealer_pushes_22(&self) -> bool {
+        let total = self.dealer.total();
+        self.variant.pushes_on_dealer_22() && total.bust && total.best == 22
+    }
+
+    /**
+     * Pay a player blackjack that ties a dealer blackjack, by game mode
+     */
+    fn blackjack_against_dealer(&self, bet: Money) -> Money {
+        match self.variant {
+            // the bet is returned
+            Variant::Classic | Variant::Switch | Variant::FreeBet => bet,
+            // player blackjack still wins 3:2
+            Variant::Spanish21 => bet + bet.scale(3, 2),
+            // player blackjack wins ties, at even money
+            Variant::DoubleExposure => bet * 2,
+            // the dealer wins ties, even between pontoons
+            Variant::Pontoon => Money::ZERO,
+        }
+    }
+
+    /**
+     * Classic settlement. Hands must beat the dealer to win
+     */
+    fn settle_classic(&self, hand: &Hand) -> Outcome {
+        let total = hand.total();
+        let dealer_max = self.dealer.true_value();
+
+        if total.blackjack { // blackjacks win 3:2, with the bet back
+            Outcome::Blackjack(hand.bet() + hand.bet().scale(3, 2))
+        } else if total.bust {
+            Outcome::Busted
+        } else if total.best > dealer_max { // wins get 2x the stake, doubles included
+            Outcome::Win(hand.stake() * 2)
+        } else {
+            Outcome::Lost
+        }
+    }
+
+    /**
+     * Spanish 21 settlement. A player 21 always wins, and 21s made with 5 or
+     * more cards, 6-7-8, or 7-7-7 pay a bonus unless doubled.
+     * Ties push
+     */
+    fn settle_spanish21(&self, hand: &Hand) -> Outcome {
+        let bet = hand.bet();
+
+        // surrender returns half the bet. a rescued double returns the
+        // doubling stake
+        if hand.is_surrendered() {
+            return match hand.is_doubled() {
+                true  => Outcome::Surrendered(bet),
+                false => Outcome::Surrendered(bet.scale(1, 2)),
+            };
+        }
+        let total = hand.total();
+        if total.bust {
+            return Outcome::Busted;
+        }
+
+        // total staked on the hand
+        let stake = hand.stake();
+
+        let value = total.best;
+        let dealer_max = self.dealer.true_value();
+        if total.blackjack {
+            Outcome::Blackjack(bet + bet.scale(3, 2))
+        } else if value == 21 {
+            match (hand.is_doubled(), spanish21_bonus(hand)) {
+                (false, Some((num, den, name))) => Outcome::Bonus(stake + bet.scale(num, den), name),
+                _ => Outcome::Win(stake * 2),
             }
+        } else if value > dealer_max {
+            Outcome::Win(stake * 2)
+        } else if value == dealer_max {
+            Outcome::Push(stake)
+        } else {
+            Outcome::Lost
+        }
+    }
+
+    /**
+     * Blackjack Switch settlement. Blackjack pays 1:1, a dealer 22 pushes all
+     * other hands that did not bust, and ties push
+     */
+    fn settle_switch(&self, hand: &Hand) -> Outcome {
+        let bet = hand.bet();
+        let total = hand.total();
+        if total.bust {
+            return Outcome::Busted;
+        }
+
+        // total staked on the hand
+        let stake = hand.stake();
+
+        let value = total.best;
+        let dealer_max = self.dealer.true_value();
+        if total.blackjack {
+            Outcome::Blackjack(bet * 2)
+        } else if self.dealer_pushes_22() {
+            Outcome::Push(stake)
+        } else if value > dealer_max {
+            Outcome::Win(stake * 2)
+        } else if value == dealer_max {
+            Outcome::Push(stake)
+        } else {
+            Outcome::Lost
         }
+    }
+
+    /**
+     * Free Bet settlement. Stakes put up by the house only pay their
+     * winnings, a dealer 22 pushes all other hands that did not bust, and
+     * ties push
+     */
+    fn settle_free_bet(&self, hand: &Hand) -> Outcome {
+        let bet = hand.bet();
+        let total = hand.total();
+        if total.bust {
+            return Outcome::Busted;
+        }
+
+        // total staked on the hand, and the part the player paid for
+        let stake = hand.stake();
+        let paid = stake - hand.free_stake();
+
+        let value = total.best;
+        let dealer_max = self.dealer.true_value();
+        if total.blackjack {
+            Outcome::Blackjack(paid + bet.scale(3, 2))
+        } else if self.dealer_pushes_22() {
+            Outcome::Push(paid)
+        } else if value > dealer_max {
+            Outcome::Win(paid + stake)
+        } else if value == dealer_max {
+            Outcome::Push(paid)
+        } else {
+            Outcome::Lost
+        }
+    }
+
+    /**
+     * Pontoon settlement. Hands are ranked, with pontoons and five-card tricks
+     * paying 2:1, and the dealer wins ties
+     */
+    fn settle_pontoon(&self, hand: &Hand) -> Outcome {
+        // total staked on the hand, including bought cards
+        let stake = hand.stake();
+
+        let rank = hand.pontoon_rank();
+        if rank == PontoonRank::Bust {
+            return Outcome::Busted;
+        }
+        if rank <= self.dealer.pontoon_rank() {
+            return Outcome::Lost;
+        }
+
+        match rank {
+            PontoonRank::Pontoon       => Outcome::Bonus(stake * 3, "Pontoon"),
+            PontoonRank::FiveCardTrick => Outcome::Bonus(stake * 3, "Five-Card Trick"),
+            _ => Outcome::Win(stake * 2),
+        }
+    }
+
+    /**
+     * Double Exposure settlement. Blackjack pays 1:1, and the dealer wins
+     * every other tie
+     */
+    fn settle_double_exposure(&self, hand: &Hand) -> Outcome {
+        let bet = hand.bet();
+        let total = hand.total();
+        if total.bust {
+            return Outcome::Busted;
+        }
+
+        // total staked on the hand
+        let stake = hand.stake();
+
+        if total.blackjack {
+            Outcome::Blackjack(bet * 2)
+        } else if total.best > self.dealer.true_value() {
+            Outcome::Win(stake * 2)
+        } else {
+            Outcome::Lost
+        }
+    }
+}
+
+/**
+ * Describe a hand on one line with its total, ex: `Hand 2: %8, #10 ; (hard 18)`.
+ * Screen readers get a sentence, ex: `Hand 2: eight of clubs, ten of hearts, hard 18.`
+ */
+fn describe_hand(label: &str, hand: &Hand) -> String {
+    match screen_reader() {
+        true  => format!("{}: {}, {}.", label, hand, hand.total()),
+        false => format!("{}: {} ; ({})", label, hand, hand.total()),
+    }
+}
+
+/**
+ * Find the Spanish 21 bonus a 21 pays, as `num:den` odds and a name.
+ * Returns None if the hand has no bonus
+ */
+fn spanish21_bonus(hand: &Hand) -> Option<(i64, i64, &'static str)> {
+    let cards = hand.cards();
+
+    // 6-7-8 and 7-7-7 pay more when suited, and most in spades
+    let mut ranks: Vec<u32> = cards.iter().map(|c| c.val).collect();
+    ranks.sort();
+    if ranks == [6, 7, 8] || ranks == [7, 7, 7] {
+        let suited = cards.iter().all(|c| c.suit == cards[0].suit);
+        return match (suited, cards[0].suit) {
+            (true, Suit::Spades) => Some((3, 1, "Spaded 6-7-8/7-7-7")),
+            (true, _)            => Some((2, 1, "Suited 6-7-8/7-7-7")),
+            (false, _)           => Some((3, 2, "6-7-8/7-7-7")),
+        };
+    }
+
+    // many card 21s
+    match cards.len() {
+        5      => Some((3, 2, "5-Card 21")),
+        6      => Some((2, 1, "6-Card 21")),
+        7..    => Some((3, 1, "7-Card 21")),
+        _      => None,
+    }
+}
+
+impl Player for Chosen {
+    fn play(&mut self, turn: &Turn) -> Action {
+        let mut strategy: Strategy = basic_strategy;
+        match self.action.take() {
+            Some(action) => action,
+            None => strategy.play(turn),
+        }
+    }
+}
+
+impl Outcome {
+    /**
+     * Get the amount paid back for the hand
+     */
+    fn payout(&self) -> Money {
+        match self {
+            Outcome::Blackjack(m) | Outcome::Win(m) | Outcome::Bonus(m, _) | Outcome::Push(m) | Outcome::Surrendered(m) | Outcome::Charlie(m) => *m,
+            Outcome::Busted | Outcome::Lost => Money::ZERO,
+        }
+    }
+
+    /**
+     * Get the name of the result, ex: `win`, `push`
+     */
+    fn name(&self, hand: &Hand) -> &'static str {
+        match (self, hand.is_doubled()) {
+            (Outcome::Blackjack(_), _)       => "blackjack",
+            (Outcome::Win(_), _)             => "win",
+            (Outcome::Bonus(_, _), _)        => "bonus",
+            (Outcome::Push(_), _)            => "push",
+            (Outcome::Surrendered(_), true)  => "rescued",
+            (Outcome::Surrendered(_), false) => "surrendered",
+            (Outcome::Charlie(_), _)         => "charlie",
+            (Outcome::Busted, _)             => "busted",
+            (Outcome::Lost, _)               => "lost",
+        }
+    }
+
+    /**
+     * Returns whether the hand beat the dealer
+     */
+    fn is_win(&self) -> bool {
+        matches!(self, Outcome::Blackjack(_) | Outcome::Win(_) | Outcome::Bonus(_, _) | Outcome::Charlie(_))
+    }
+
+    /**
+     * Get the colored result tag shown for a hand
+     */
+    fn tag(&self, hand: &Hand) -> String {
+        match (self, hand.is_doubled()) {
+            (Outcome::Blackjack(_), _)     => String::from("[38;5;220m[Blackjack][0m"),
+            (Outcome::Win(_), true)        => String::from("[38;5;40m[Win][38;5;220m[x2][0m"),
+            (Outcome::Win(_), false)       => String::from("[38;5;40m[Win][0m"),
+            (Outcome::Bonus(_, name), _)   => format!("[38;5;40m[Win][38;5;220m[{}][0m", name),
+            (Outcome::Push(_), _)          => String::from("[38;5;214m[Push][0m"),
+            (Outcome::Surrendered(_), true)  => String::from("[38;5;214m[Rescued][0m"),
+            (Outcome::Surrendered(_), false) => String::from("[38;5;214m[Surrendered][0m"),
+            (Outcome::Charlie(_), _)       => String::from("[38;5;220m[Charlie][0m"),
+            (Outcome::Busted, _)           => String::from("[38;5;196m[Busted][0m"),
+            (Outcome::Lost, _)             => String::from("[38;5;196m[Lost][0m"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(codes: &str, bet: Money) -> Hand {
+        let mut hand = Hand::from_codes(codes);
+        hand.set_bet(bet);
+        hand
+    }
+
+    fn game(variant: Variant, dealer: &str) -> Game {
+        let settings = GameSettings { variant, charlie: None, ..GameSettings::default() };
+        let mut game = Game::new(&settings);
+        game.dealer = hand(dealer, Money::ZERO);
+        game
+    }
+
+    #[test]
+    fn classic_blackjack_pays_3_to_2() {
+        let game = game(Variant::Classic, "10S 8H");
+        let outcome = game.settle_hand(&hand("AS KH", Money::from_dollars(100)));
+        assert_eq!(outcome.name(&hand("AS KH", Money::ZERO)), "blackjack");
+        assert_eq!(outcome.payout(), Money::from_dollars(250));
+
+        // odd bets round to the cent
+        let outcome = game.settle_hand(&hand("AS KH", Money::from_dollars(25)));
+        assert_eq!(outcome.payout(), "$62.50".parse().unwrap());
+    }
+
+    #[test]
+    fn blackjack_pays_the_same_in_classic_spanish21_and_free_bet() {
+        let bet = Money::from_dollars(50);
+        let paid: Vec<Money> = [Variant::Classic, Variant::Spanish21, Variant::FreeBet].iter()
+            .map(|variant| game(*variant, "9S 8H").settle_hand(&hand("AS KH", bet)).payout())
+            .collect();
+        assert_eq!(paid, vec![Money::from_dollars(125); 3]);
+    }
+
+    #[test]
+    fn classic_wins_pay_even_money() {
+        let game = game(Variant::Classic, "10S 8H");
+        let bet = Money::from_dollars(100);
+        assert_eq!(game.settle_hand(&hand("10C 9D", bet)).payout(), Money::from_dollars(200));
+        assert_eq!(game.settle_hand(&hand("10C 7D", bet)).payout(), Money::ZERO);
+    }
+
+    #[test]
+    fn split_ace_and_ten_is_not_a_blackjack() {
+        let game = game(Variant::Classic, "10S 8H");
+        let mut split = hand("AS KH", Money::from_dollars(100));
+        split.set_split(true);
+        let outcome = game.settle_hand(&split);
+        assert_eq!(outcome.payout(), Money::from_dollars(200));
+        assert_eq!(outcome.name(&split), "win");
+        assert_eq!(split.total().to_string(), "soft 21");
+    }
+
+    #[test]
+    fn split_ace_and_ten_is_not_a_pontoon() {
+        let game = game(Variant::Pontoon, "10S 7H");
+        let bet = Money::from_dollars(100);
+        let mut split = hand("AS KH", bet);
+        split.set_split(true);
+        assert_eq!(split.pontoon_rank(), PontoonRank::Points(21));
+        assert_eq!(game.settle_hand(&split).payout(), Money::from_dollars(200));
+        assert_eq!(game.settle_hand(&hand("AS KH", bet)).payout(), Money::from_dollars(300));
+    }
+
+    #[test]
+    fn pontoon_hands_keep_buying_to_the_five_card_trick() {
+        let game = game(Variant::Pontoon, "10S 7H");
+        let bet = Money::from_dollars(100);
+        let can_buy = |hand: &Hand| game.play_options(hand, bet).iter().any(|(_, action)| *action == Action::Double);
+
+        let mut bought = hand("2S 3H 4D", bet);
+        bought.buy();
+        bought.buy();
+        assert!(can_buy(&bought));
+        assert_eq!(bought.stake(), Money::from_dollars(300));
+        assert!(!can_buy(&hand("2S 3H 4D 2C 5C", bet)));
+
+        // each buy is staked on the win
+        bought.give_card(Some("10C".parse().unwrap()));
+        assert_eq!(game.settle_hand(&bought).payout(), Money::from_dollars(600));
+    }
+
+    // play one free bet round on one seat by basic strategy, dealing `cards` first
+    fn free_bet_round(cards: &str, bet: Money) -> RoundResult {
+        let settings = GameSettings { variant: Variant::FreeBet, speed: DealSpeed::Instant, ..GameSettings::default() };
+        let mut game = Game::new(&settings);
+        game.set_strategy(basic_strategy);
+        game.stack_deck(Hand::from_codes(cards).cards()).unwrap();
+        crate::util::term::set_quiet(true);
+        game.play(&[SeatBet { main: bet, side: vec![] }], bet)
+    }
+
+    #[test]
+    fn free_split_loss_costs_nothing() {
+        // 8-8 splits for free against a dealer 19, and both hands lose
+        let result = free_bet_round("8S 10C 8H 9D 10S 10H", Money::from_dollars(100));
+        assert_eq!(result.hands.len(), 2);
+        assert_eq!(result.bought, 0);
+        assert!(result.spent.is_zero());
+        assert!(result.payout.is_zero());
+    }
+
+    #[test]
+    fn free_double_win_pays_on_the_house_stake() {
+        // hard 11 doubles for free and makes 21 against a dealer 19: the bet
+        // back, with even money on the bet and the house's stake
+        let result = free_bet_round("6S 10C 5H 9D 10S", Money::from_dollars(100));
+        assert_eq!(result.hands[0].stake, Money::from_dollars(200));
+        assert_eq!(result.bought, 0);
+        assert!(result.spent.is_zero());
+        assert_eq!(result.payout, Money::from_dollars(300));
+    }
+
+    #[test]
+    fn free_stakes_are_never_paid_back() {
+        let bet = Money::from_dollars(100);
+        let mut split = hand("8S 10D", bet);
+        split.set_split(true);
+        split.set_free_split(true);
+        let beaten = game(Variant::FreeBet, "10S 7H");
+        assert_eq!(beaten.settle_hand(&split).payout(), Money::from_dollars(100));
 
-        // sum the payouts and return
-        payouts.iter().sum()
+        // a dealer 22 pushes only the part the player paid for
+        let pushed = game(Variant::FreeBet, "10S 6H 6D");
+        assert!(pushed.settle_hand(&split).payout().is_zero());
+        assert_eq!(pushed.settle_hand(&hand("10C 7D", bet)).payout(), bet);
     }
 }
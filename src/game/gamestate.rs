@@ -1,16 +1,23 @@
 use std::{cmp, fs::File, io::{Read, Write}};
 use serde::{Serialize, Deserialize};
-use prompted::input;
 
-use crate::util::input::{read_one_char, validated_input};
+use crate::{cards::{card::Card, deck::ShoeMode, hand::{Hand, SplitRule}, shuffle::{HumanShuffle, ShuffleStyle}, suit::Suit, theme::{set_theme, theme, CardTheme}}, util::{input::{field_hint, field_input, read_one_char, step_fields, validated_input, yes_no_field, Field}, term::{set_four_color, set_screen_reader, tinput, tprint, tprintln}}};
 
-use super::{game::Game, settings::GameSettings, bank::GameBank};
+use super::{config::with_config, game::Game, settings::{CharlieRule, DealSpeed, GameSettings}, bank::GameBank, money::Money, chips::{format_breakdown, run_betting_ui}, sidebets::{SideBetKind, SideBetRule}, variant::Variant};
 
 // settings and state for the game
 #[derive(Serialize, Deserialize)]
 pub struct GameState {
     settings: Option<GameSettings>,
     pub bank: GameBank,
+    #[serde(skip)]
+    scenario: Option<Vec<Card>>,
+    #[serde(skip)]
+    practice: bool,     // money does not count, and nothing is saved
+    #[serde(skip)]
+    force_screen_reader: bool,  // from the command line, over any settings
+    #[serde(skip)]
+    force_four_color: bool,     // from the command line, over any settings
 }
 
 impl GameState {
@@ -19,21 +26,75 @@ impl GameState {
      * no history
      */
     pub fn new() -> GameState {
-        return GameState {
+        GameState {
             settings: None,
             bank: GameBank::new(),
+            scenario: None,
+            practice: false,
+            force_screen_reader: false,
+            force_four_color: false,
         }
     }
 
+    /**
+     * Make a practice table with `settings` and any scenario of this one.
+     * It has a bank of its own at the starting balance, and is never saved,
+     * so money won or lost does not count
+     */
+    pub fn for_practice(&self, settings: GameSettings) -> GameState {
+        let mut practice = GameState::new();
+        practice.scenario = self.scenario.clone();
+        practice.practice = true;
+        practice.force_display(self.force_screen_reader, self.force_four_color);
+        practice.use_settings(settings);
+        practice
+    }
+
+    /**
+     * Set the cards to stack the shoe with at the start of each game.
+     * Not saved
+     */
+    pub fn set_scenario(&mut self, cards: Vec<Card>) {
+        self.scenario = Some(cards);
+    }
+
+    /**
+     * Turn on the screen reader or the four-color deck whatever the settings
+     * played with say, as the command line flags do. Not saved
+     */
+    pub fn force_display(&mut self, screen_reader: bool, four_color: bool) {
+        self.force_screen_reader = screen_reader;
+        self.force_four_color = four_color;
+        if screen_reader {
+            set_screen_reader(true);
+        }
+        if four_color {
+            set_four_color(true);
+        }
+    }
+
+    /**
+     * Get the settings last played with, if any
+     */
+    pub fn settings(&self) -> Option<&GameSettings> {
+        self.settings.as_ref()
+    }
+
     pub fn can_start(&self) -> bool {
         match &self.settings {
-            Some(s) => {
-                s.hand_count * self.bank.cur_bet <= self.bank.get_balance()
-            },
-            None => false,
+            Some(s) => self.can_afford(s),
+            None    => false,
         }
     }
 
+    /**
+     * Returns whether the balance covers the minimum bet on every hand of
+     * `settings`
+     */
+    fn can_afford(&self, settings: &GameSettings) -> bool {
+        min_bet() * settings.hand_count * settings.variant.hands_per_seat() <= self.bank.get_balance()
+    }
+
     /**
      * Start setup to begin a new game.
      * Used if no games currently exist, typically coming from the main menu
@@ -48,8 +109,17 @@ impl GameState {
             None    => return,
         };
 
-        // make a new game from the settings
+        // make a new game from the settings, dealing any scenario first
         let mut game = Game::new(settings);
+        game.set_practice(self.practice);
+        let stacked = match &self.scenario {
+            Some(cards) => game.stack_deck(cards),
+            None        => Ok(()),
+        };
+        if let Err(err) = stacked {
+            tinput!("Scenario: {}.\nEnter to continue...", err);
+            return;
+        }
 
         // play the game
         self.play_game(&mut game);
@@ -66,23 +136,28 @@ impl GameState {
             };
 
             // calculate some display numbers
-            let again_cost = self.bank.cur_bet * settings.hand_count;
-            let again_bal = self.bank.get_balance() as i32 - again_cost as i32;
-            let can_again = again_bal >= 0;
+            let again_hands = settings.hand_count * settings.variant.hands_per_seat();
+            let again_cost = min_bet() * again_hands;
+            let again_bal = self.bank.get_balance() - again_cost;
+            let can_again = again_bal >= Money::ZERO;
+            let practice = match self.practice {
+                true  => "Practice table, money does not count\n",
+                false => "",
+            };
 
             // display the play again menu based on balance. get input
             if can_again {
-                print!(
-                    "[2JYou now have ${}\nIt costs ${} to play {} more hands\nYou will be left with ${}\n\n1. Play Again\n2. Change Settings\n3. Main Menu\n:: ",
-                    self.bank.get_balance(), again_cost, settings.hand_count, again_bal
+                tprint!(
+                    "[2J{}You now have {}\nIt costs at least {} to play {} more hands\nYou will be left with at most {}\n\n1. Play Again\n2. Change Settings\n3. Main Menu\n:: ",
+                    practice, self.bank.get_balance(), again_cost, again_hands, again_bal
                 );
             } else {
-                print!(
-                    "[2JYou now have ${}\nIt costs ${} to play {} more hands.\nYou do not have enough to play again, please change settings or incur a balance reset.\n\n1. Reset Balance\n2. Change Settings\n3. Main Menu\n:: ",
-                    self.bank.get_balance(), again_cost, settings.hand_count
+                tprint!(
+                    "[2J{}You now have {}\nIt costs at least {} to play {} more hands.\nYou do not have enough to play again, please change settings or incur a balance reset.\n\n1. Reset Balance\n2. Change Settings\n3. Main Menu\n:: ",
+                    practice, self.bank.get_balance(), again_cost, again_hands
                 );
             };
-            let again = validated_input(|c| '1' <= c && c <= '3', |inp| 1 <= inp && inp <= 3);
+            let again = validated_input(|c| ('1'..='3').contains(&c), |inp| (1..=3).contains(&inp));
 
             match again {
                 // play again
@@ -92,24 +167,21 @@ impl GameState {
                         self.play_game(&mut game);
                     } else {
                         self.bank.reset_balance();
-                        input!(
-                            "[2JBalance reset to ${}. You now have {} resets.\n\nEnter to continue...",
+                        tinput!(
+                            "[2JBalance reset to {}. You now have {} resets.\n\nEnter to continue...",
                             self.bank.get_balance(), self.bank.get_resets()
                         );
                     }
                 },
                 // new settings
                 2 => {
-                    // get new settings
-                    self.new_settings();
-
-                    // save or back out if backed out
-                    match &self.settings {
-                        Some(s) => {
-                            game.update_settings(&s);
-                            self.play_game(&mut game);
-                        },
-                        None    => ()
+                    // get new settings, keeping the last ones if backed out
+                    if !self.new_settings() {
+                        continue;
+                    }
+                    if let Some(s) = &self.settings {
+                        game.update_settings(s);
+                        self.play_game(&mut game);
                     }
                 } 
                 // back to main menu
@@ -121,7 +193,9 @@ impl GameState {
     }
     
     /**
-     * Play a game loop
+     * Play a game loop.
+     * Bets are placed with chips before the deal; backing out of betting
+     * skips the round
      */
     fn play_game(&mut self, game: &mut Game) {
         // if there are no settings panic, shouldn't be possible
@@ -130,92 +204,243 @@ impl GameState {
             None    => panic!("No settings present in GameState::play_game."),
         };
 
-        // take balance away for purchased hands
-        self.bank.buy(settings.hand_count);
+        // place a bet on each seat, with any offered side bets
+        let side_bets: Vec<SideBetKind> = settings.side_bets.iter().map(|rule| rule.kind).collect();
+        let per_seat = settings.variant.hands_per_seat();
+        let bets = match run_betting_ui(self.bank.get_balance(), settings.hand_count, per_seat, min_bet(), &side_bets, &self.bank.last_bets) {
+            Some(bets) => bets,
+            None       => return,
+        };
+        self.bank.last_bets = bets.clone();
+
+        // take balance away for purchased hands, then for each side bet
+        self.bank.buy(bets.iter().map(|bet| bet.main * per_seat).sum(), settings.hand_count * per_seat);
+        for bet in bets.iter() {
+            for (kind, amount) in bet.side.iter() {
+                self.bank.buy_side_bet(*kind, *amount);
+            }
+        }
 
-        // calc amount of spare hands, play the game
-        let spare_hands = self.bank.get_balance() / self.bank.cur_bet;
-        let (wins, bought_hands) = game.play(spare_hands);
+        // play the game with the remaining balance available to buy hands
+        let result = game.play(&bets, self.bank.get_balance());
         
         // pay for bought hands
-        if bought_hands > 0 {
-            self.bank.buy(bought_hands);
+        if result.bought > 0 {
+            self.bank.buy(result.spent, result.bought);
         }
 
-        // print winnings and update bank
-        if wins == 0 as f32 {
-            input!("\nYou didn't win anything...\nYou now have ${}\n\nEnter to continue...", self.bank.get_balance());
+        // show and pay out each side bet
+        if !result.side_bets.is_empty() {
+            tprintln!("\nSide bets:");
+        }
+        for side_bet in result.side_bets.iter() {
+            tprintln!("{}", side_bet);
+            if !side_bet.payout.is_zero() {
+                self.bank.win_side_bet(side_bet.kind, side_bet.payout);
+            }
+        }
+
+        // print winnings as chips and update bank
+        let wins = result.payout;
+        if wins.is_zero() {
+            tinput!("\nYou didn't win anything...\nYou now have {}\n\nEnter to continue...", self.bank.get_balance());
         } else {
-            input!("\nYou won back ${}\nYou now have ${}\n\nEnter to continue...", self.bank.win(wins), self.bank.get_balance());
+            let won = self.bank.win(wins);
+            tinput!("\nYou won back {}\nPaid out: {}\nYou now have {}\n\nEnter to continue...", won, format_breakdown(won), self.bank.get_balance());
         }
 
-        _ = self.save_state();
+        if !self.practice {
+            _ = self.save_state();
+        }
     }
 
     /**
-     * Create settings to use for a game.
-     * Is set to `None` if backed out at the end
+     * Create settings to use for a game, one question at a time. Each
+     * question starts on the answer last used.
+     * Up goes back a question. Escape, or cancelling at the end, leaves the
+     * settings as they were.
+     * Returns whether new settings were confirmed
      */
-    pub fn new_settings(&mut self) {
+    pub fn new_settings(&mut self) -> bool {
         // if there is not enough money to buy at least 1 hand, attempt a reset
-        if self.bank.get_balance() < 50 {
-            print!("\n[2JYou do not have enough money to buy any hands. Reset your balance? [y/n]\n:: ");
-            loop {
-                match read_one_char() {
-                    'y' => {
-                        self.bank.reset_balance();
-                        break;
-                    },
-                    'n' => return,
-                     _  => return, // shouldnt be here
-                }
+        if self.bank.get_balance() < min_bet() {
+            tprint!("\n[2JYou do not have enough money to buy any hands. Reset your balance? [y/n]\n:: ");
+            match read_one_char() {
+                'y' => self.bank.reset_balance(),
+                'n' => return false,
+                 _  => return false, // shouldnt be here
             }
         }
 
-        // calc max hands based on balance, or 7
-        let max_hands = cmp::min(self.bank.get_balance() / 50, 7);
+        // the answers so far, starting from the last settings
+        let last = self.settings.as_ref();
+        let mut variant = last.map(|s| s.variant);
+        let mut deck_count = last.map(|s| s.deck_count);
+        let mut hand_count = last.map(|s| s.hand_count);
+        let mut shoe = last.map(|s| s.shoe);
+        let mut shuffle = last.map(|s| s.shuffle);
+        let mut charlie = last.map(|s| s.charlie);
+        let mut split_rule = last.map(|s| s.split_rule);
+        let mut side_bets = last.map(|s| s.side_bets.clone());
+        let mut card_theme = last.map(|s| s.theme);
+        let mut speed = last.map(|s| s.speed);
+        let mut four_color = last.map(|s| s.four_color);
+        let mut screen_reader = last.map(|s| s.screen_reader);
+        let balance = self.bank.get_balance();
 
-        // get the deck count. 1 <= x <= 16
-        print!("\n[2JYou have ${}.\n\nDecks to use (1-16):\nHands to play (1-{}):\nAmount to bet (>=$50): $\n[3A[21C", self.bank.get_balance(), max_hands);
-        let deck_count = validated_input(|c| c >= '0' && c <= '9', |deck| deck != 0 && deck <= 16);
+        let answered = step_fields(13, |step, forward| match step {
+            // pick the game mode
+            0 => choose_variant(variant).map(|v| variant = Some(v)),
+            // get the deck count. 1 <= x <= 16, unless configured.
+            // a game mode the balance cannot seat once goes back to the
+            // game mode, as no hand count would be accepted
+            1 if max_hands(balance, variant) == 0 => {
+                let variant = variant.unwrap_or_default();
+                tinput!(
+                    "\n[2J{} costs at least {} to play a seat, more than your {}.\nPick another game mode.\n\nEnter to go back...",
+                    variant, min_bet() * variant.hands_per_seat(), balance
+                );
+                Field::Back
+            },
+            1 => {
+                let max_decks = with_config(|config| config.table.max_decks);
+                let prompt = format!("Decks to use (1-{}): ", max_decks);
+                tprint!("\n[2J{}You have {}.\n\n{}\nHands to play (1-{}):\n[2A[{}C", field_hint(), balance, prompt.trim_end(), max_hands(balance, variant), prompt.len());
+                field_input(|c| c.is_ascii_digit(), |deck| deck != 0 && deck <= max_decks, deck_count).map(|d| deck_count = Some(d))
+            },
+            // get the hand count. checks for valid amount based on balance.
+            // 1 <= x <= 7, unless configured
+            2 => {
+                let max_decks = with_config(|config| config.table.max_decks);
+                let max_hands = max_hands(balance, variant);
+                tprint!("\n[2J{}You have {}.\n\nDecks to use (1-{}): {}\nHands to play (1-{}): ", field_hint(), balance, max_decks, deck_count.unwrap_or(1), max_hands);
+                field_input(|c| c.is_ascii_digit(), |hand| hand != 0 && hand <= max_hands, hand_count).map(|h| hand_count = Some(h))
+            },
+            // pick how the shoe is shuffled. shufflers always shuffle perfectly
+            3 => choose_shoe(deck_count.unwrap_or(1), shoe).map(|s| shoe = Some(s)),
+            4 => match shoe {
+                Some(ShoeMode::Shoe) => choose_shuffle(shuffle).map(|s| shuffle = Some(s)),
+                _ => {
+                    shuffle = Some(ShuffleStyle::Perfect);
+                    Field::skip(forward)
+                },
+            },
+            // pick a charlie rule, or none, and which hands can split
+            5 => choose_charlie(charlie).map(|c| charlie = Some(c)),
+            6 => choose_split_rule(split_rule).map(|s| split_rule = Some(s)),
+            // pick a paytable for each side bet, or turn it off
+            7 => choose_side_bets(side_bets.clone()).map(|b| side_bets = Some(b)),
+            // pick how cards are drawn, previewing each theme, and how the
+            // game is read
+            8 => choose_theme(card_theme).map(|t| card_theme = Some(t)),
+            9 => choose_speed(speed).map(|s| speed = Some(s)),
+            10 => {
+                tprint!("\n[2J{}Use the four-color deck? [y/n]\n:: ", field_hint());
+                yes_no_field(four_color).map(|f| four_color = Some(f))
+            },
+            11 => {
+                tprint!("\nScreen reader mode, without screen clears or colors? [y/n]\n:: ");
+                yes_no_field(screen_reader).map(|s| screen_reader = Some(s))
+            },
+            // confirm settings
+            _ => {
+                let display = match (four_color, screen_reader) {
+                    (Some(true), Some(true)) => "four-color deck, screen reader",
+                    (Some(true), _)          => "four-color deck",
+                    (_, Some(true))          => "screen reader",
+                    _                        => "standard",
+                };
+                let charlie_text = match charlie.flatten() {
+                    Some(rule) => rule.to_string(),
+                    None       => String::from("none"),
+                };
 
-        // get the hand count. checks for valid amount based on balance.
-        // 1 <= x <= 7
-        print!("\nHands to play (1-{}):\nAmount to bet (>=$50): $\n[2F[21C[0K", max_hands);
-        let hand_count = validated_input(|c| c >= '0' && c <= '9', |hand| hand != 0 && hand <= max_hands);
+                // describe the offered side bets for confirmation
+                let offered: Vec<String> = side_bets.iter().flatten().map(|rule| format!("{} ({})", rule.kind, rule.paytable.name)).collect();
+                let offered = match offered.is_empty() {
+                    true  => String::from("none"),
+                    false => offered.join(", "),
+                };
 
-        // get bet. min val 50
-        print!("\n[1A[1BAmount to bet (>=$50): $\n[1F[24C[0K");
-        let bet_amount = validated_input(|c| c >= '0' && c <= '9', |bet| bet >= 50 && bet * hand_count <= self.bank.get_balance());
-        println!("[1A");
+                // display confirmation info. bets are placed with chips each
+                // round
+                let per_seat = variant.unwrap_or_default().hands_per_seat();
+                let hands = hand_count.unwrap_or(1);
+                tprint!("[2J{}You have {}.\nPlaying {} with:\n{} decks in a {},\nShuffled with a {},\n{} hands at a minimum of {} each ({}).\nCharlie: {}.\nSplits: {}.\nSide bets: {}.\nCards: {} ({}).\nDealing: {}.\nBets are placed with chips before each deal.\n\n1. Confirm\n2. Cancel\n:: ",
+                    field_hint(), balance, variant.unwrap_or_default(), deck_count.unwrap_or(1), shoe.unwrap_or_default(), shuffle.unwrap_or_default(), hands, min_bet() * per_seat, min_bet() * hands * per_seat,
+                    charlie_text, split_rule.unwrap_or_default(), offered, card_theme.unwrap_or_default(), display, speed.unwrap_or_default());
+                match field_input(|c| c == '1' || c == '2', |inp| inp == 1 || inp == 2, None) {
+                    Field::Value(1) => Field::Value(()),
+                    Field::Value(_) => Field::Cancel,
+                    field => field.map(|_| ()),
+                }
+            },
+        });
 
-        // confirm settings. in loop in case of invalid input
-        loop {
-            // display confirmation info
-            let play_cost = hand_count * bet_amount;
-            print!("[2JBalance remaining after start: ${}\nPlaying with:\n{} decks,\n{} hands at ${} each (${}).\n\n1. Confirm\n2. Cancel\n:: ",
-                self.bank.get_balance() - play_cost, deck_count, hand_count, bet_amount, play_cost);
-            let input = validated_input(|c| c == '1' || c == '2', |inp| inp == 1 || inp == 2);
-
-            match input {
-                // confirm; create settings
-                1 => {
-                    self.settings = Some(GameSettings {
-                        deck_count,
-                        hand_count,
-                    });
-                    self.bank.cur_bet = bet_amount;
-                    return;
-                },
-                // cancel; set to None
-                2 => {
-                    self.settings = None;
-                    return;
-                },
-                // go again
-                _ => (),
+        // create settings once confirmed. cancelling keeps the last ones
+        let (Field::Value(()), Some(variant), Some(deck_count), Some(hand_count), Some(shoe), Some(shuffle), Some(charlie), Some(split_rule), Some(side_bets), Some(card_theme), Some(speed), Some(four_color), Some(screen_reader))
+            = (answered, variant, deck_count, hand_count, shoe, shuffle, charlie, split_rule, side_bets, card_theme, speed, four_color, screen_reader) else {
+            return false;
+        };
+        self.use_settings(GameSettings {
+            variant,
+            deck_count,
+            hand_count,
+            shoe,
+            shuffle,
+            charlie,
+            split_rule,
+            side_bets,
+            theme: card_theme,
+            four_color,
+            screen_reader,
+            speed,
+        });
+        true
+    }
+
+    /**
+     * Pick a table preset from the config, or choose each setting. Presets
+     * the balance cannot cover are dimmed and cannot be picked.
+     * Returns whether settings were picked
+     */
+    pub fn choose_table(&mut self) -> bool {
+        let presets = with_config(|config| config.presets.clone());
+        if presets.is_empty() {
+            return self.new_settings();
+        }
+
+        // list each preset after choosing by hand
+        tprint!("\n[2J{}Table:\n1. Custom - choose each setting\n", field_hint());
+        for (i, preset) in presets.iter().enumerate() {
+            match self.can_afford(&preset.settings) {
+                true  => tprintln!("{}. {} - {}", i + 2, preset.name, preset.summary()),
+                false => tprintln!("[2m{}. {} - {}[0m", i + 2, preset.name, preset.summary()),
             }
         }
+        tprint!(":: ");
+
+        let picked = field_input(|c| c.is_ascii_digit(), |inp: usize| {
+            inp == 1 || presets.get(inp.wrapping_sub(2)).is_some_and(|preset| self.can_afford(&preset.settings))
+        }, None);
+        match picked {
+            Field::Value(1) => self.new_settings(),
+            Field::Value(choice) => {
+                self.use_settings(presets[choice - 2].settings.clone());
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /**
+     * Play with `settings` from now on, drawing cards as they ask
+     */
+    pub fn use_settings(&mut self, settings: GameSettings) {
+        set_theme(settings.theme);
+        set_four_color(settings.four_color || self.force_four_color);
+        set_screen_reader(settings.screen_reader || self.force_screen_reader);
+        self.settings = Some(settings);
     }
 
     /**
@@ -235,7 +460,7 @@ impl GameState {
         };
 
         // write the json to save file
-        match file.write(json.as_bytes()) {
+        match file.write_all(json.as_bytes()) {
             Ok(_) =>  Ok(()),
             Err(_) => Err("Could not write to file"),
         }
@@ -260,10 +485,261 @@ impl GameState {
             Err(_) => return Err("Could not read from save file"),
         }
 
-        // parse the buffer into a GameState
-        match serde_json::from_slice(&buf) {
-            Ok(gs) => Ok(gs),
-            Err(_) => return Err("Could not deserialize the save file")
+        // parse the buffer into a GameState, then draw cards in its theme
+        match serde_json::from_slice::<GameState>(&buf) {
+            Ok(gs) => {
+                if let Some(s) = &gs.settings {
+                    set_theme(s.theme);
+                    set_four_color(s.four_color);
+                    set_screen_reader(s.screen_reader);
+                }
+                Ok(gs)
+            },
+            Err(_) => Err("Could not deserialize the save file")
         }
     }
 }
+
+/**
+ * The smallest bet allowed at the table
+ */
+pub fn min_bet() -> Money {
+    with_config(|config| config.table.min_bet)
+}
+
+/**
+ * The most hands `balance` can buy in `variant`, up to the table's limit.
+ * Each seat may play several hands
+ */
+fn max_hands(balance: Money, variant: Option<Variant>) -> usize {
+    let per_seat = variant.unwrap_or_default().hands_per_seat();
+    cmp::min(balance.count_of(min_bet() * per_seat), with_config(|config| config.table.max_hands))
+}
+
+/**
+ * Choose the game mode to play, starting on `last`.
+ * Returns the chosen mode
+ */
+fn choose_variant(last: Option<Variant>) -> Field<Variant> {
+    // list each mode with its rules
+    tprint!("\n[2J{}Game mode:\n", field_hint());
+    for (i, variant) in Variant::ALL.iter().enumerate() {
+        tprintln!("{}. {} - {}", i + 1, variant, variant.description());
+    }
+    tprint!(":: ");
+
+    let last = last.and_then(|last| Variant::ALL.iter().position(|v| *v == last)).map(|i| i + 1);
+    field_input(|c| c.is_ascii_digit(), |inp: usize| inp != 0 && inp <= Variant::ALL.len(), last)
+        .map(|choice| Variant::ALL[choice - 1])
+}
+
+/**
+ * Choose between a hand-shuffled shoe and a continuous shuffler, and how many
+ * cards the shuffler holds back, starting on `last`.
+ * A shuffler can hold up to a quarter of the `decks` decks
+ */
+fn choose_shoe(decks: usize, last: Option<ShoeMode>) -> Field<ShoeMode> {
+    let mut choice = last.map(|shoe| match shoe {
+        ShoeMode::Shoe => 1,
+        ShoeMode::Continuous { .. } => 2,
+    });
+    let mut buffer = match last {
+        Some(ShoeMode::Continuous { buffer }) => Some(buffer),
+        _ => None,
+    };
+
+    // the most recent discards wait in the machine before going back in
+    let max_buffer = decks * 13;
+    step_fields(2, |step, forward| match step {
+        0 => {
+            tprint!("\n[2J{}Shuffling:\n1. Hand-shuffled shoe - discards are shuffled back in when the shoe runs out\n2. Continuous shuffler - discards go back in after every round\n:: ", field_hint());
+            field_input(|c| c == '1' || c == '2', |inp| inp == 1 || inp == 2, choice).map(|c| choice = Some(c))
+        },
+        _ if choice == Some(1) => Field::skip(forward),
+        _ => {
+            tprint!("\nCards held in the shuffler (0-{}):\n:: ", max_buffer);
+            field_input(|c| c.is_ascii_digit(), |buffer| buffer <= max_buffer, buffer).map(|b| buffer = Some(b))
+        },
+    }).map(|()| match (choice, buffer) {
+        (Some(2), Some(buffer)) => ShoeMode::Continuous { buffer },
+        _ => ShoeMode::Shoe,
+    })
+}
+
+/**
+ * Choose between a perfect shuffle and a simulated dealer shuffle, either
+ * typical or with each step chosen, starting on `last`
+ */
+fn choose_shuffle(last: Option<ShuffleStyle>) -> Field<ShuffleStyle> {
+    let mut choice = last.map(|shuffle| match shuffle {
+        ShuffleStyle::Perfect => 1,
+        ShuffleStyle::Human(steps) if steps == HumanShuffle::TYPICAL => 2,
+        ShuffleStyle::Human(_) => 3,
+    });
+    let mut steps = match last {
+        Some(ShuffleStyle::Human(steps)) => steps,
+        _ => HumanShuffle::TYPICAL,
+    };
+
+    // each step of the shuffle, then where the cut card goes. the spread
+    // biases how far it can be off
+    step_fields(8, |step, forward| match step {
+        0 => {
+            tprint!("\n[2J{}Shuffle:\n1. Perfect - every order is equally likely\n2. Dealer - a typical casino shuffle, riffles, strip cut, box, and plug\n3. Dealer (custom) - choose each step\n:: ", field_hint());
+            field_input(|c| ('1'..='3').contains(&c), |inp| (1..=3).contains(&inp), choice).map(|c| choice = Some(c))
+        },
+        _ if choice != Some(3) => Field::skip(forward),
+        1 => {
+            tprint!("\nRiffles (1-9):\n:: ");
+            field_input(|c| c.is_ascii_digit(), |inp| (1..=9).contains(&inp), Some(steps.riffles)).map(|v| steps.riffles = v)
+        },
+        2 => {
+            tprint!("\nClumping, the percent chance cards fall together (0-99):\n:: ");
+            field_input(|c| c.is_ascii_digit(), |inp| inp <= 99, Some(steps.clumping)).map(|v| steps.clumping = v)
+        },
+        3 => {
+            tprint!("\nStrip cut packets, 0 to skip (0-9):\n:: ");
+            field_input(|c| c.is_ascii_digit(), |inp| inp <= 9, Some(steps.strip_cuts)).map(|v| steps.strip_cuts = v)
+        },
+        4 => {
+            tprint!("\nBox shuffle? [y/n]\n:: ");
+            yes_no_field(Some(steps.box_shuffle)).map(|v| steps.box_shuffle = v)
+        },
+        5 => {
+            tprint!("\nPlug undealt cards into the discards? [y/n]\n:: ");
+            yes_no_field(Some(steps.plug)).map(|v| steps.plug = v)
+        },
+        6 => {
+            tprint!("\nPercent of the shoe dealt before the cut card (10-95):\n:: ");
+            field_input(|c| c.is_ascii_digit(), |inp| (10..=95).contains(&inp), Some(steps.penetration)).map(|v| steps.penetration = v)
+        },
+        _ => {
+            tprint!("\nCards the cut card can miss by (0-52):\n:: ");
+            field_input(|c| c.is_ascii_digit(), |inp| inp <= 52, Some(steps.cut_spread)).map(|v| steps.cut_spread = v)
+        },
+    }).map(|()| match choice {
+        Some(1) => ShuffleStyle::Perfect,
+        Some(2) => ShuffleStyle::Human(HumanShuffle::TYPICAL),
+        _ => ShuffleStyle::Human(steps),
+    })
+}
+
+/**
+ * Choose whether hands win automatically at 5, 6, or 7 cards, and what that
+ * pays, starting on `last`.
+ * Returns None for no Charlie rule
+ */
+fn choose_charlie(last: Option<Option<CharlieRule>>) -> Field<Option<CharlieRule>> {
+    let mut cards = last.map(|rule| rule.map_or(0, |rule| rule.cards));
+    let mut pays = last.flatten().map(|rule| match (rule.num, rule.den) {
+        (1, 1) => 1,
+        (3, 2) => 2,
+        _      => 3,
+    });
+
+    step_fields(2, |step, forward| match step {
+        0 => {
+            tprint!("\n[2J{}Charlie rule, a hand of this many cards without busting wins:\n0. Off\n5. 5-Card Charlie\n6. 6-Card Charlie\n7. 7-Card Charlie\n:: ", field_hint());
+            field_input(|c| c.is_ascii_digit(), |inp| inp == 0 || (5..=7).contains(&inp), cards).map(|c| cards = Some(c))
+        },
+        _ if cards == Some(0) => Field::skip(forward),
+        _ => {
+            tprint!("\nCharlie pays:\n1. 1:1\n2. 3:2\n3. 2:1\n:: ");
+            field_input(|c| ('1'..='3').contains(&c), |inp| (1..=3).contains(&inp), pays).map(|p| pays = Some(p))
+        },
+    }).map(|()| {
+        let cards = cards.filter(|cards| *cards != 0)?;
+        let (num, den) = match pays {
+            Some(1) => (1, 1),
+            Some(2) => (3, 2),
+            _       => (2, 1),
+        };
+        Some(CharlieRule { cards, num, den })
+    })
+}
+
+/**
+ * Choose which two card hands can be split, starting on `last`
+ */
+fn choose_split_rule(last: Option<SplitRule>) -> Field<SplitRule> {
+    tprint!("\n[2J{}Splitting:\n1. Same rank only - K-K splits, K-Q does not\n2. Any two ten-value cards - K-Q and 10-J split too\n:: ", field_hint());
+    let last = last.map(|rule| match rule {
+        SplitRule::SameRank => 1,
+        SplitRule::AnyTen   => 2,
+    });
+    field_input(|c| c == '1' || c == '2', |inp| inp == 1 || inp == 2, last).map(|choice| match choice {
+        1 => SplitRule::SameRank,
+        _ => SplitRule::AnyTen,
+    })
+}
+
+/**
+ * Choose how cards are drawn, starting on `last`. Each theme is shown with a
+ * sample hand.
+ * The current theme is kept while choosing
+ */
+fn choose_theme(last: Option<CardTheme>) -> Field<CardTheme> {
+    // a sample hand of an ace and a ten
+    let mut sample = Hand::new();
+    sample.give_card(Some(Card::from_rank(1, Suit::Spades)));
+    sample.give_card(Some(Card::from_rank(10, Suit::Hearts)));
+
+    let current = theme();
+    tprint!("\n[2J{}Card theme:\n", field_hint());
+    for (i, card_theme) in CardTheme::ALL.iter().enumerate() {
+        set_theme(*card_theme);
+        tprintln!("{}. {} - {}", i + 1, card_theme, sample);
+    }
+    set_theme(current);
+    tprint!(":: ");
+
+    let last = last.and_then(|last| CardTheme::ALL.iter().position(|t| *t == last)).map(|i| i + 1);
+    field_input(|c| c.is_ascii_digit(), |inp: usize| inp != 0 && inp <= CardTheme::ALL.len(), last)
+        .map(|choice| CardTheme::ALL[choice - 1])
+}
+
+/**
+ * Choose how fast cards are dealt, starting on `last`
+ */
+fn choose_speed(last: Option<DealSpeed>) -> Field<DealSpeed> {
+    tprint!("\n[2J{}Dealing speed:\n1. Instant - every card appears at once\n2. Fast - cards are dealt one at a time\n3. Casino - paced like a dealer at a table\n:: ", field_hint());
+    let last = last.and_then(|last| DealSpeed::ALL.iter().position(|s| *s == last)).map(|i| i + 1);
+    field_input(|c| c.is_ascii_digit(), |inp: usize| inp != 0 && inp <= DealSpeed::ALL.len(), last)
+        .map(|choice| DealSpeed::ALL[choice - 1])
+}
+
+/**
+ * Choose the paytable for each side bet, or leave it off the table, starting
+ * on `last`.
+ * Returns the side bets offered
+ */
+fn choose_side_bets(last: Option<Vec<SideBetRule>>) -> Field<Vec<SideBetRule>> {
+    // the paytable last picked for each side bet, 0 for off
+    let mut choices: Vec<Option<usize>> = SideBetKind::ALL.iter().map(|kind| {
+        let rules = last.as_ref()?;
+        let picked = rules.iter()
+            .find(|rule| rule.kind == *kind)
+            .and_then(|rule| kind.paytables().iter().position(|paytable| paytable.name == rule.paytable.name));
+        Some(picked.map_or(0, |i| i + 1))
+    }).collect();
+
+    step_fields(SideBetKind::ALL.len(), |step, _| {
+        // list the paytables, 0 turns the side bet off
+        let kind = SideBetKind::ALL[step];
+        let paytables = kind.paytables();
+        tprint!("\n[2J{}{} side bet:\n0. Off\n", field_hint(), kind);
+        for (i, paytable) in paytables.iter().enumerate() {
+            tprintln!("{}. {}", i + 1, paytable);
+        }
+        tprint!(":: ");
+
+        field_input(|c| c.is_ascii_digit(), |inp: usize| inp <= paytables.len(), choices[step]).map(|c| choices[step] = Some(c))
+    }).map(|()| {
+        SideBetKind::ALL.into_iter().zip(&choices)
+            .filter_map(|(kind, choice)| {
+                let paytable = kind.paytables().into_iter().nth(choice.unwrap_or(0).wrapping_sub(1))?;
+                Some(SideBetRule { kind, paytable })
+            })
+            .collect()
+    })
+}
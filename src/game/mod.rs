@@ -1,4 +1,13 @@
+#[allow(clippy::module_inception)]
 pub mod game;
 pub mod gamestate;
 pub mod settings;
 pub mod bank;
+pub mod money;
+pub mod chips;
+pub mod sidebets;
+pub mod variant;
+pub mod table;
+pub mod config;
+pub mod strategy;
+pub mod player;
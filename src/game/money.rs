@@ -0,0 +1,365 @@
+use std::{fmt::Display, iter::Sum, ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign}, str::FromStr};
+
+use serde::{de::{self, Visitor}, Deserialize, Deserializer, Serialize, Serializer};
+
+/**
+ * A fixed-point amount of money, stored as a whole number of cents.
+ * Displays and parses as dollars, ex: `$12.50`.
+ * Arithmetic is checked: an amount past the range of `i64` cents panics in
+ * every build rather than wrapping
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    /**
+     * No money at all
+     */
+    pub const ZERO: Money = Money(0);
+
+    /**
+     * Create an amount from a whole number of dollars
+     */
+    pub const fn from_dollars(dollars: i64) -> Money {
+        Money(dollars * 100)
+    }
+
+    /**
+     * Get the whole dollars of the amount, dropping any cents
+     */
+    pub fn dollars(&self) -> i64 {
+        self.0 / 100
+    }
+
+    /**
+     * Get the amount as a whole number of cents
+     */
+    pub fn cents(&self) -> i64 {
+        self.0
+    }
+
+    /**
+     * Returns whether the amount is exactly $0.00
+     */
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /**
+     * Scale the amount by the ratio `num:den`, ex: `scale(3, 2)` for a 3:2
+     * payout.
+     * Rounds half a cent away from zero, so no cents are lost to truncation
+     */
+    pub fn scale(&self, num: i64, den: i64) -> Money {
+        // widen so large balances cannot overflow mid-calculation
+        let scaled = self.0 as i128 * num as i128;
+        let den = den as i128;
+
+        // round to the nearest cent, halves away from zero
+        let half = den.abs() / 2;
+        let rounded = match (scaled < 0) == (den < 0) {
+            true  => (scaled.abs() + half) / den.abs(),
+            false => -((scaled.abs() + half) / den.abs()),
+        };
+
+        Money(rounded as i64)
+    }
+
+    /**
+     * Add `rhs`, or None if the sum overflows
+     */
+    pub fn checked_add(&self, rhs: Money) -> Option<Money> {
+        self.0.checked_add(rhs.0).map(Money)
+    }
+
+    /**
+     * Multiply by a count, or None if the product overflows
+     */
+    pub fn checked_mul(&self, rhs: usize) -> Option<Money> {
+        i64::try_from(rhs).ok().and_then(|rhs| self.0.checked_mul(rhs)).map(Money)
+    }
+
+    /**
+     * Total `amounts`, each paid `times` times, ex: a bet on each seat for
+     * every hand of the seat.
+     * Returns None if the total overflows
+     */
+    pub fn checked_total(amounts: &[Money], times: usize) -> Option<Money> {
+        amounts.iter().try_fold(Money::ZERO, |total, amount| total.checked_add(*amount))?.checked_mul(times)
+    }
+
+    /**
+     * Returns how many whole `unit`s fit in this amount.
+     * Returns 0 if `unit` is not a positive amount
+     */
+    pub fn count_of(&self, unit: Money) -> usize {
+        if unit.0 <= 0 || self.0 <= 0 {
+            return 0;
+        }
+
+        (self.0 / unit.0) as usize
+    }
+}
+
+// arithmetic between amounts. panics on overflow, see `Money`
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        self.checked_add(rhs).expect("money overflowed")
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0.checked_sub(rhs.0).expect("money overflowed"))
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.checked_neg().expect("money overflowed"))
+    }
+}
+
+// an amount times a count, ex: bet * hands
+impl Mul<usize> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: usize) -> Money {
+        self.checked_mul(rhs).expect("money overflowed")
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |acc, m| acc + m)
+    }
+}
+
+// make money printable as `$12.50` or `-$12.50`
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        let text = format!("{}${}.{:02}", sign, cents / 100, cents % 100);
+
+        // pad so money lines up in tables
+        f.pad(&text)
+    }
+}
+
+/**
+ * Reasons an amount of money could not be parsed
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoneyParseError {
+    Empty,
+    InvalidDigit,
+    TooManyCents,
+    TooLarge,
+}
+
+impl FromStr for Money {
+    type Err = MoneyParseError;
+
+    /**
+     * Parse an amount such as `12`, `12.5`, `$1,234.50` or `-$3.00`.
+     * Commas may group the dollars. At most two decimal places are allowed
+     */
+    fn from_str(s: &str) -> Result<Money, MoneyParseError> {
+        // peel off the sign and dollar symbol
+        let s = s.trim();
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None       => (false, s),
+        };
+        let s = s.strip_prefix('$').unwrap_or(s);
+
+        // split into dollars and cents
+        let (dollars, cents) = match s.split_once('.') {
+            Some((d, c)) => (d, c),
+            None         => (s, ""),
+        };
+
+        if dollars.is_empty() && cents.is_empty() {
+            return Err(MoneyParseError::Empty);
+        }
+        if cents.len() > 2 {
+            return Err(MoneyParseError::TooManyCents);
+        }
+        if dollars.starts_with(',') || dollars.ends_with(',') {
+            return Err(MoneyParseError::InvalidDigit);
+        }
+        let dollars = dollars.replace(',', "");
+        if !dollars.chars().chain(cents.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(MoneyParseError::InvalidDigit);
+        }
+
+        // convert each part, `5` cents means 50
+        let dollars: i64 = match dollars.as_str() {
+            "" => 0,
+            d  => d.parse().map_err(|_| MoneyParseError::TooLarge)?,
+        };
+        let cents: i64 = match cents.len() {
+            0 => 0,
+            1 => cents.parse::<i64>().unwrap() * 10,
+            _ => cents.parse().unwrap(),
+        };
+
+        // combine, checking for overflow
+        let total = dollars.checked_mul(100)
+            .and_then(|d| d.checked_add(cents))
+            .ok_or(MoneyParseError::TooLarge)?;
+
+        Ok(Money(if negative { -total } else { total }))
+    }
+}
+
+// saved as a dollar string so saves stay readable, ex: "$12.50"
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        /**
+         * Accepts both dollar strings and the whole dollar integers used by
+         * older saves
+         */
+        struct MoneyVisitor;
+
+        impl Visitor<'_> for MoneyVisitor {
+            type Value = Money;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a dollar amount such as \"$12.50\" or a whole number of dollars")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Money, E> {
+                i64::try_from(v).ok()
+                    .and_then(|d| d.checked_mul(100))
+                    .map(Money)
+                    .ok_or_else(|| E::custom("amount too large"))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Money, E> {
+                v.checked_mul(100)
+                    .map(Money)
+                    .ok_or_else(|| E::custom("amount too large"))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Money, E> {
+                v.parse().map_err(|e| E::custom(format!("invalid amount {v:?}: {e:?}")))
+            }
+        }
+
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dollars_and_cents() {
+        assert_eq!("12".parse(), Ok(Money::from_dollars(12)));
+        assert_eq!("12.5".parse(), Ok(Money(1250)));
+        assert_eq!("$12.05".parse(), Ok(Money(1205)));
+        assert_eq!(".75".parse(), Ok(Money(75)));
+        assert_eq!("$1,234.5".parse(), Ok(Money(123450)));
+        assert_eq!(" $1,000,000 ".parse(), Ok(Money::from_dollars(1_000_000)));
+    }
+
+    #[test]
+    fn parses_negatives() {
+        assert_eq!("-$3.00".parse(), Ok(Money(-300)));
+        assert_eq!("-0.5".parse(), Ok(Money(-50)));
+        assert_eq!("$-3".parse::<Money>(), Err(MoneyParseError::InvalidDigit));
+    }
+
+    #[test]
+    fn rejects_bad_amounts() {
+        assert_eq!("".parse::<Money>(), Err(MoneyParseError::Empty));
+        assert_eq!("$".parse::<Money>(), Err(MoneyParseError::Empty));
+        assert_eq!("1.234".parse::<Money>(), Err(MoneyParseError::TooManyCents));
+        assert_eq!("12a".parse::<Money>(), Err(MoneyParseError::InvalidDigit));
+        assert_eq!(",100".parse::<Money>(), Err(MoneyParseError::InvalidDigit));
+        assert_eq!("1.,5".parse::<Money>(), Err(MoneyParseError::InvalidDigit));
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        // i64::MAX cents is $92233720368547758.07
+        assert_eq!("92233720368547758.07".parse(), Ok(Money(i64::MAX)));
+        assert_eq!("92233720368547758.08".parse::<Money>(), Err(MoneyParseError::TooLarge));
+        assert_eq!("99999999999999999999".parse::<Money>(), Err(MoneyParseError::TooLarge));
+    }
+
+    #[test]
+    fn scale_rounds_half_away_from_zero() {
+        assert_eq!(Money(1000).scale(3, 2), Money(1500));
+        assert_eq!(Money(5).scale(3, 2), Money(8));     // 7.5 cents
+        assert_eq!(Money(-5).scale(3, 2), Money(-8));
+        assert_eq!(Money(100).scale(1, 3), Money(33));
+        assert_eq!(Money(200).scale(1, 3), Money(67));
+        assert_eq!(Money(i64::MAX).scale(1, 2), Money(i64::MAX / 2 + 1));
+    }
+
+    #[test]
+    fn displays_as_dollars() {
+        assert_eq!(Money(1250).to_string(), "$12.50");
+        assert_eq!(Money(5).to_string(), "$0.05");
+        assert_eq!(Money(-300).to_string(), "-$3.00");
+        assert_eq!(format!("{:>8}", Money(100)), "   $1.00");
+    }
+
+    #[test]
+    fn loads_old_integer_saves() {
+        assert_eq!(serde_json::from_str::<Money>("100").unwrap(), Money::from_dollars(100));
+        assert_eq!(serde_json::from_str::<Money>("-20").unwrap(), Money::from_dollars(-20));
+        assert_eq!(serde_json::from_str::<Money>("\"$12.50\"").unwrap(), Money(1250));
+        assert!(serde_json::from_str::<Money>("92233720368547759").is_err());
+        assert_eq!(serde_json::to_string(&Money(1250)).unwrap(), "\"$12.50\"");
+    }
+
+    #[test]
+    fn checked_total_catches_overflow() {
+        let big = Money(i64::MAX / 2 + 1);
+        assert_eq!(Money::checked_total(&[Money(500), Money(250)], 2), Some(Money(1500)));
+        assert_eq!(Money::checked_total(&[], 2), Some(Money::ZERO));
+        assert_eq!(Money::checked_total(&[big, big], 1), None);
+        assert_eq!(Money::checked_total(&[big], 2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "money overflowed")]
+    fn overflow_panics() {
+        let _ = Money(i64::MAX) + Money(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "money overflowed")]
+    fn multiply_overflow_panics() {
+        let _ = Money(i64::MAX / 2) * 3;
+    }
+}
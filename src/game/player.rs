@@ -0,0 +1,52 @@
+use crate::{cards::{card::Card, hand::Hand}, util::keys::Action};
+
+use super::{money::Money, settings::CharlieRule, table::TableView};
+
+/**
+ * Everything known when a hand is to be played: the hand, the dealer's
+ * upcard, the plays offered, and the table as it would be drawn
+ */
+pub struct Turn<'a> {
+    pub index: usize,                           // which hand is being played
+    pub owner: usize,                           // the wallet paying for the hand's seat
+    pub hand: &'a Hand,
+    pub hands: &'a [Hand],                      // every hand at the table, as last played
+    pub upcard: Option<&'a Card>,               // none when the dealer's cards are all face down
+    pub dealer: &'a [Card],                     // every dealer card face up, ex: both in double exposure
+    pub charlie: Option<CharlieRule>,           // the table's charlie rule, if any
+    pub options: &'a [(&'static str, Action)],  // each play offered, with its label
+    pub available: Money,                       // left to spend on the hand's seat
+    pub table: TableView,                       // with an empty action bar
+}
+
+/**
+ * Plays hands in place of the keyboard: a strategy, a bot, or a player at
+ * another table
+ */
+pub trait Player {
+    /**
+     * Pick a play for the hand in `turn`.
+     * Must return one of `turn.options`
+     */
+    fn play(&mut self, turn: &Turn) -> Action;
+}
+
+impl Turn<'_> {
+    /**
+     * Returns whether `action` is one of the plays offered
+     */
+    pub fn offers(&self, action: Action) -> bool {
+        self.options.iter().any(|(_, offered)| *offered == action)
+    }
+
+    /**
+     * Get the play to fall back on when none is given: stand if offered,
+     * otherwise the first option
+     */
+    pub fn fallback(&self) -> Action {
+        match self.offers(Action::Stand) {
+            true  => Action::Stand,
+            false => self.options[0].1,
+        }
+    }
+}
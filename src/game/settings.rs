@@ -1,10 +1,136 @@
+use std::{fmt::Display, time::Duration};
+
 use serde::{Deserialize, Serialize};
 
+use crate::cards::{deck::ShoeMode, hand::SplitRule, shuffle::ShuffleStyle, theme::CardTheme};
+
+use super::{sidebets::{SideBetKind, SideBetRule}, variant::Variant};
+
 /**
- * Simple game settings, tracking deck and hand counts
+ * Simple game settings, tracking the game mode, deck and hand counts, how the
+ * shoe is shuffled, table rules, the side bets offered at the table, how
+ * cards are drawn and read, and how fast they are dealt
  */
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameSettings {
+    #[serde(default)]
+    pub variant: Variant,
     pub deck_count: usize,
     pub hand_count: usize,
+    #[serde(default)]
+    pub shoe: ShoeMode,
+    #[serde(default)]
+    pub shuffle: ShuffleStyle,
+    #[serde(default)]
+    pub charlie: Option<CharlieRule>,
+    #[serde(default)]
+    pub split_rule: SplitRule,
+    #[serde(default = "default_side_bets")]
+    pub side_bets: Vec<SideBetRule>,
+    #[serde(default)]
+    pub theme: CardTheme,
+    #[serde(default)]
+    pub four_color: bool,
+    #[serde(default)]
+    pub screen_reader: bool,
+    #[serde(default)]
+    pub speed: DealSpeed,
+}
+
+/**
+ * How fast cards are dealt on screen
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DealSpeed {
+    // every card appears at once
+    #[default]
+    Instant,
+    // a short pause after each card
+    Fast,
+    // paced like a dealer at a table
+    Casino,
+}
+
+impl DealSpeed {
+    /**
+     * All speeds, in the order they are listed in settings
+     */
+    pub const ALL: [DealSpeed; 3] = [DealSpeed::Instant, DealSpeed::Fast, DealSpeed::Casino];
+
+    /**
+     * The pause after each card is dealt or flipped
+     */
+    pub fn delay(&self) -> Duration {
+        match self {
+            DealSpeed::Instant => Duration::ZERO,
+            DealSpeed::Fast => Duration::from_millis(150),
+            DealSpeed::Casino => Duration::from_millis(600),
+        }
+    }
+
+    /**
+     * Returns whether cards are shown one at a time
+     */
+    pub fn is_paced(&self) -> bool {
+        !matches!(self, DealSpeed::Instant)
+    }
+}
+
+/**
+ * A Charlie table rule: a hand reaching `cards` cards without busting wins
+ * automatically, paying `num:den`
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharlieRule {
+    pub cards: usize,
+    pub num: i64,
+    pub den: i64,
+}
+
+impl Default for GameSettings {
+    /**
+     * One hand of classic blackjack from six decks, with every side bet on its
+     * standard paytable
+     */
+    fn default() -> GameSettings {
+        GameSettings {
+            variant: Variant::default(),
+            deck_count: 6,
+            hand_count: 1,
+            shoe: ShoeMode::default(),
+            shuffle: ShuffleStyle::default(),
+            charlie: None,
+            split_rule: SplitRule::default(),
+            side_bets: default_side_bets(),
+            theme: CardTheme::default(),
+            four_color: false,
+            screen_reader: false,
+            speed: DealSpeed::default(),
+        }
+    }
+}
+
+/**
+ * Offer every side bet on its standard paytable
+ */
+fn default_side_bets() -> Vec<SideBetRule> {
+    SideBetKind::ALL.iter().map(|kind| SideBetRule::standard(*kind)).collect()
+}
+
+// make a deal speed printable
+impl Display for DealSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            DealSpeed::Instant => "Instant",
+            DealSpeed::Fast => "Fast",
+            DealSpeed::Casino => "Casino",
+        })
+    }
+}
+
+// show a charlie rule. ex: 5-Card Charlie pays 1:1
+impl Display for CharlieRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-Card Charlie pays {}:{}", self.cards, self.num, self.den)
+    }
 }
@@ -0,0 +1,303 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cards::card::Card;
+
+use super::money::Money;
+
+/**
+ * Each side bet that can be placed on a seat before the deal
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SideBetKind {
+    TwentyOnePlusThree,
+    PerfectPairs,
+}
+
+/**
+ * Each winning side bet hand
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SideBetHand {
+    // 21+3 hands
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+    SuitedTrips,
+    // perfect pairs hands
+    MixedPair,
+    ColoredPair,
+    PerfectPair,
+}
+
+/**
+ * How much each winning hand of a side bet pays, as `n` to 1
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Paytable {
+    pub name: String,
+    pub pays: Vec<(SideBetHand, u32)>,
+}
+
+/**
+ * A side bet offered at the table, with its paytable
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SideBetRule {
+    pub kind: SideBetKind,
+    pub paytable: Paytable,
+}
+
+/**
+ * The outcome of one side bet on one seat
+ */
+pub struct SideBetResult {
+    pub seat: usize,
+    pub kind: SideBetKind,
+    pub stake: Money,
+    pub hand: Option<SideBetHand>,
+    pub payout: Money,
+}
+
+impl SideBetKind {
+    /**
+     * All side bets, in the order they are shown at the table
+     */
+    pub const ALL: [SideBetKind; 2] = [SideBetKind::TwentyOnePlusThree, SideBetKind::PerfectPairs];
+
+    /**
+     * Evaluate the side bet from a seat's first two cards and the dealer's
+     * upcard.
+     * Returns the winning hand, or None if the bet lost
+     */
+    pub fn evaluate(&self, cards: &[Card], upcard: &Card) -> Option<SideBetHand> {
+        match (self, cards) {
+            (SideBetKind::TwentyOnePlusThree, [a, b, ..]) => evaluate_three_card_poker(&[*a, *b, *upcard]),
+            (SideBetKind::PerfectPairs, [a, b, ..])       => evaluate_pair(a, b),
+            _ => None,
+        }
+    }
+
+    /**
+     * The paytables this side bet can be played with.
+     * The first is the standard table
+     */
+    pub fn paytables(&self) -> Vec<Paytable> {
+        match self {
+            SideBetKind::TwentyOnePlusThree => vec![
+                Paytable::new("Standard", &[
+                    (SideBetHand::SuitedTrips, 100),
+                    (SideBetHand::StraightFlush, 40),
+                    (SideBetHand::ThreeOfAKind, 30),
+                    (SideBetHand::Straight, 10),
+                    (SideBetHand::Flush, 5),
+                ]),
+                Paytable::new("Flat 9:1", &[
+                    (SideBetHand::SuitedTrips, 9),
+                    (SideBetHand::StraightFlush, 9),
+                    (SideBetHand::ThreeOfAKind, 9),
+                    (SideBetHand::Straight, 9),
+                    (SideBetHand::Flush, 9),
+                ]),
+            ],
+            SideBetKind::PerfectPairs => vec![
+                Paytable::new("Standard", &[
+                    (SideBetHand::PerfectPair, 25),
+                    (SideBetHand::ColoredPair, 12),
+                    (SideBetHand::MixedPair, 6),
+                ]),
+                Paytable::new("30/10/5", &[
+                    (SideBetHand::PerfectPair, 30),
+                    (SideBetHand::ColoredPair, 10),
+                    (SideBetHand::MixedPair, 5),
+                ]),
+            ],
+        }
+    }
+}
+
+impl Paytable {
+    /**
+     * Create a named paytable from `(hand, n to 1)` pairs
+     */
+    pub fn new(name: &str, pays: &[(SideBetHand, u32)]) -> Paytable {
+        Paytable {
+            name: String::from(name),
+            pays: pays.to_vec(),
+        }
+    }
+
+    /**
+     * Get the `n` to 1 odds a hand pays.
+     * Returns None if the hand does not pay on this table
+     */
+    pub fn odds(&self, hand: SideBetHand) -> Option<u32> {
+        self.pays.iter().find(|(h, _)| *h == hand).map(|(_, n)| *n)
+    }
+}
+
+impl SideBetRule {
+    /**
+     * The standard rule for a side bet
+     */
+    pub fn standard(kind: SideBetKind) -> SideBetRule {
+        SideBetRule {
+            kind,
+            paytable: kind.paytables().remove(0),
+        }
+    }
+
+    /**
+     * Settle `stake` on this side bet for `seat`.
+     * Wins return the stake plus the odds; losses return nothing
+     */
+    pub fn settle(&self, seat: usize, stake: Money, cards: &[Card], upcard: &Card) -> SideBetResult {
+        // find the hand, then what it pays on this table
+        let hand = self.kind.evaluate(cards, upcard);
+        let payout = match hand.and_then(|h| self.paytable.odds(h)) {
+            Some(n) => stake + stake * n as usize,
+            None    => Money::ZERO,
+        };
+
+        SideBetResult { seat, kind: self.kind, stake, hand, payout }
+    }
+}
+
+/**
+ * Evaluate three cards as a 21+3 poker hand
+ */
+fn evaluate_three_card_poker(cards: &[Card; 3]) -> Option<SideBetHand> {
+    let suited = cards.iter().all(|c| c.suit == cards[0].suit);
+    let trips = cards.iter().all(|c| c.val == cards[0].val);
+
+    // sort ranks to look for a run. aces count high or low
+    let mut ranks: Vec<u32> = cards.iter().map(|c| c.val).collect();
+    ranks.sort();
+    let straight = (ranks[0] + 1 == ranks[1] && ranks[1] + 1 == ranks[2])
+        || ranks == [1, 12, 13];
+
+    match (suited, trips, straight) {
+        (true, true, _)      => Some(SideBetHand::SuitedTrips),
+        (true, _, true)      => Some(SideBetHand::StraightFlush),
+        (false, true, _)     => Some(SideBetHand::ThreeOfAKind),
+        (false, _, true)     => Some(SideBetHand::Straight),
+        (true, false, false) => Some(SideBetHand::Flush),
+        _ => None,
+    }
+}
+
+/**
+ * Evaluate two cards as a perfect pairs hand.
+ * Pairs must match by rank, so K and Q are not a pair
+ */
+fn evaluate_pair(a: &Card, b: &Card) -> Option<SideBetHand> {
+    if a.val != b.val {
+        return None;
+    }
+
+    match (a.suit == b.suit, a.suit.is_red() == b.suit.is_red()) {
+        (true, _)      => Some(SideBetHand::PerfectPair),
+        (false, true)  => Some(SideBetHand::ColoredPair),
+        (false, false) => Some(SideBetHand::MixedPair),
+    }
+}
+
+// make a side bet printable
+impl Display for SideBetKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            SideBetKind::TwentyOnePlusThree => "21+3",
+            SideBetKind::PerfectPairs => "Perfect Pairs",
+        })
+    }
+}
+
+// make a side bet hand printable
+impl Display for SideBetHand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            SideBetHand::Flush => "Flush",
+            SideBetHand::Straight => "Straight",
+            SideBetHand::ThreeOfAKind => "Three of a Kind",
+            SideBetHand::StraightFlush => "Straight Flush",
+            SideBetHand::SuitedTrips => "Suited Trips",
+            SideBetHand::MixedPair => "Mixed Pair",
+            SideBetHand::ColoredPair => "Colored Pair",
+            SideBetHand::PerfectPair => "Perfect Pair",
+        })
+    }
+}
+
+// show a paytable as its name and each payout. ex: Standard (Perfect Pair 25:1, ...)
+impl Display for Paytable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pays: Vec<String> = self.pays.iter().map(|(hand, n)| format!("{} {}:1", hand, n)).collect();
+        write!(f, "{} ({})", self.name, pays.join(", "))
+    }
+}
+
+// show a side bet result with its result tag
+impl Display for SideBetResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.hand {
+            Some(hand) if !self.payout.is_zero() => write!(f, "Seat {} {}: {} bet, [38;5;220m[{}][0m +{}", self.seat + 1, self.kind, self.stake, hand, self.payout),
+            _ => write!(f, "Seat {} {}: {} bet, [38;5;196m[Lost][0m", self.seat + 1, self.kind, self.stake),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::hand::Hand;
+
+    fn settle(kind: SideBetKind, codes: &str, upcard: &str) -> SideBetResult {
+        let hand = Hand::from_codes(codes);
+        SideBetRule::standard(kind).settle(0, Money::from_dollars(10), hand.cards(), &upcard.parse().unwrap())
+    }
+
+    #[test]
+    fn twenty_one_plus_three_hands() {
+        let hand = |codes: &str, upcard: &str| SideBetKind::TwentyOnePlusThree.evaluate(Hand::from_codes(codes).cards(), &upcard.parse().unwrap());
+        assert_eq!(hand("7H 7H", "7H"), Some(SideBetHand::SuitedTrips));
+        assert_eq!(hand("8S 9S", "10S"), Some(SideBetHand::StraightFlush));
+        assert_eq!(hand("7H 7S", "7C"), Some(SideBetHand::ThreeOfAKind));
+        assert_eq!(hand("QH KS", "AC"), Some(SideBetHand::Straight));
+        assert_eq!(hand("AH 2S", "3C"), Some(SideBetHand::Straight));
+        assert_eq!(hand("2D 9D", "KD"), Some(SideBetHand::Flush));
+        assert_eq!(hand("KH AS", "2C"), None);
+    }
+
+    #[test]
+    fn perfect_pairs_hands() {
+        let hand = |codes: &str| SideBetKind::PerfectPairs.evaluate(Hand::from_codes(codes).cards(), &"2C".parse().unwrap());
+        assert_eq!(hand("QH QH"), Some(SideBetHand::PerfectPair));
+        assert_eq!(hand("QH QD"), Some(SideBetHand::ColoredPair));
+        assert_eq!(hand("QH QS"), Some(SideBetHand::MixedPair));
+        assert_eq!(hand("QH KH"), None);
+    }
+
+    #[test]
+    fn suited_trips_pays_100_to_1() {
+        let result = settle(SideBetKind::TwentyOnePlusThree, "7H 7H", "7H");
+        assert_eq!(result.hand, Some(SideBetHand::SuitedTrips));
+        assert_eq!(result.payout, Money::from_dollars(1010));
+    }
+
+    #[test]
+    fn paytables_set_the_odds() {
+        let flat = SideBetRule { kind: SideBetKind::TwentyOnePlusThree, paytable: SideBetKind::TwentyOnePlusThree.paytables().remove(1) };
+        let hand = Hand::from_codes("7H 7H");
+        assert_eq!(flat.settle(0, Money::from_dollars(10), hand.cards(), &"7H".parse().unwrap()).payout, Money::from_dollars(100));
+        assert_eq!(settle(SideBetKind::PerfectPairs, "QH QS", "2C").payout, Money::from_dollars(70));
+    }
+
+    #[test]
+    fn losing_side_bets_pay_nothing() {
+        let result = settle(SideBetKind::PerfectPairs, "QH KH", "2C");
+        assert_eq!(result.hand, None);
+        assert!(result.payout.is_zero());
+    }
+}
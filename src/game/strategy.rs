@@ -0,0 +1,172 @@
+use crate::{cards::{card::Card, hand::Hand}, util::keys::Action};
+
+use super::{player::{Player, Turn}, settings::CharlieRule};
+
+/**
+ * Picks a play for a hand from the options offered, knowing what is seen of
+ * the table
+ */
+pub type Strategy = fn(&Hand, &Seen, &[Action]) -> Action;
+
+/**
+ * What a strategy sees of the table: the dealer's face up cards, and the
+ * rules that change how a hand is played
+ */
+pub struct Seen<'a> {
+    pub dealer: &'a [Card],     // none when all are face down, both in double exposure
+    pub charlie: Option<CharlieRule>,
+}
+
+/**
+ * Play `hand` by multi-deck basic strategy against the dealer's cards in
+ * `seen`. With no card face up, it plays as if against a ten. With both face
+ * up, a dealer who stands must be beaten, and a dealer who draws is played
+ * as the upcard it acts like.
+ * With a Charlie rule, a hand one card short hits when it cannot bust or is
+ * more likely than not to make the Charlie.
+ * When the chart's play is not offered, doubles fall back to hitting, or to
+ * standing on soft 18 and up, and surrenders fall back to hitting. Pontoon
+ * hands twist when they cannot stick
+ */
+pub fn basic_strategy(hand: &Hand, seen: &Seen, options: &[Action]) -> Action {
+    let total = hand.total();
+    let pick = |plays: &[Action]| plays.iter().copied().find(|action| options.contains(action)).unwrap_or(options[0]);
+
+    // one card from a charlie wins outright. hard 14 and under bust on at
+    // most 6 of 13 ranks
+    if seen.charlie.is_some_and(|rule| total.cards + 1 == rule.cards) && (total.soft || total.best <= 14) {
+        return pick(&[Action::Hit, Action::Stand]);
+    }
+
+    // the dealer's upcard, 2 to 11 for an ace. a whole dealer hand acts
+    // like a bust card when stiff, or like its total when low
+    let up = match seen.dealer {
+        [] => 10,
+        [upcard] => match upcard.val {
+            1 => 11,
+            v => v.min(10),
+        },
+        cards => {
+            let mut dealer = Hand::new();
+            for card in cards {
+                dealer.give_card(Some(*card));
+            }
+            let dealt = dealer.total();
+            match (dealt.soft, dealt.best) {
+                // the dealer stands, so draw until beating them
+                (_, 17..) if total.best <= dealt.best => return pick(&[Action::Hit, Action::Stand]),
+                (_, 17..) => return pick(&[Action::Stand, Action::Hit]),
+                (true, _) => 7,
+                (false, 12..) => 6,
+                (false, best) => best.min(10),
+            }
+        },
+    };
+
+    // pairs first, then soft and hard totals
+    let split = match total.pair.map(|rank| rank.min(10)) {
+        Some(1)      => true,
+        Some(8)      => true,
+        Some(9)      => matches!(up, 2..=6 | 8 | 9),
+        Some(7)      => up <= 7,
+        Some(6)      => up <= 6,
+        Some(4)      => matches!(up, 5 | 6),
+        Some(2 | 3)  => up <= 7,
+        _            => false,
+    };
+
+    let play = match (split, total.soft, total.best) {
+        (true, _, _)        => Action::Split,
+        (_, true, 20..)     => Action::Stand,
+        (_, true, 19)       => if up == 6 { Action::Double } else { Action::Stand },
+        (_, true, 18)       => match up {
+            2..=6 => Action::Double,
+            7 | 8 => Action::Stand,
+            _     => Action::Hit,
+        },
+        (_, true, 17)       => if (3..=6).contains(&up) { Action::Double } else { Action::Hit },
+        (_, true, 15 | 16)  => if (4..=6).contains(&up) { Action::Double } else { Action::Hit },
+        (_, true, _)        => if (5..=6).contains(&up) { Action::Double } else { Action::Hit },
+        (_, false, 17..)    => Action::Stand,
+        (_, false, 16) if up >= 9  => Action::Surrender,
+        (_, false, 15) if up == 10 => Action::Surrender,
+        (_, false, 13..=16) => if up <= 6 { Action::Stand } else { Action::Hit },
+        (_, false, 12)      => if (4..=6).contains(&up) { Action::Stand } else { Action::Hit },
+        (_, false, 11)      => Action::Double,
+        (_, false, 10)      => if up <= 9 { Action::Double } else { Action::Hit },
+        (_, false, 9)       => if (3..=6).contains(&up) { Action::Double } else { Action::Hit },
+        _                   => Action::Hit,
+    };
+
+    // fall back to what is offered
+    let fallback = match play {
+        Action::Double if total.soft && total.best >= 18 => Action::Stand,
+        Action::Split if total.best >= 17 => Action::Stand,
+        Action::Split if (12..=16).contains(&total.best) && up <= 6 => Action::Stand,
+        _ => Action::Hit,
+    };
+    [play, fallback, Action::Hit, Action::Stand].into_iter()
+        .find(|action| options.contains(action))
+        .unwrap_or(options[0])
+}
+
+// a strategy sees only the hand, the dealer's face up cards, the charlie
+// rule, and the plays offered
+impl Player for Strategy {
+    fn play(&mut self, turn: &Turn) -> Action {
+        let options: Vec<Action> = turn.options.iter().map(|(_, action)| *action).collect();
+        let seen = Seen { dealer: turn.dealer, charlie: turn.charlie };
+        self(turn.hand, &seen, &options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYS: [Action; 4] = [Action::Hit, Action::Stand, Action::Double, Action::Split];
+
+    fn play(player: &str, dealer: &str, charlie: Option<CharlieRule>) -> Action {
+        let dealer = Hand::from_codes(dealer);
+        basic_strategy(&Hand::from_codes(player), &Seen { dealer: dealer.cards(), charlie }, &PLAYS)
+    }
+
+    #[test]
+    fn plays_the_upcard_chart() {
+        assert_eq!(play("10S 6H", "6C", None), Action::Stand);
+        assert_eq!(play("10S 6H", "10C", None), Action::Hit);
+        assert_eq!(play("6S 5H", "9C", None), Action::Double);
+        assert_eq!(play("8S 8H", "10C", None), Action::Split);
+    }
+
+    #[test]
+    fn plays_as_against_a_ten_when_nothing_is_face_up() {
+        assert_eq!(play("10S 6H", "", None), Action::Hit);
+        assert_eq!(play("6S 4H", "", None), Action::Hit);
+    }
+
+    #[test]
+    fn draws_to_beat_a_dealer_who_stands() {
+        // double exposure shows both dealer cards
+        assert_eq!(play("10S 8H", "10C 9D", None), Action::Hit);
+        assert_eq!(play("10S 8H", "10C 8D", None), Action::Hit);
+        assert_eq!(play("10S 8H", "10C 7D", None), Action::Stand);
+        assert_eq!(play("10S 2H", "AC 6D", None), Action::Hit);
+    }
+
+    #[test]
+    fn stands_stiff_against_a_dealer_who_draws() {
+        assert_eq!(play("10S 2H", "10C 6D", None), Action::Stand);
+        assert_eq!(play("6S 5H", "10C 5D", None), Action::Double);
+        assert_eq!(play("10S 6H", "5C 4D", None), Action::Hit);
+    }
+
+    #[test]
+    fn hits_for_a_charlie() {
+        let five = Some(CharlieRule { cards: 5, num: 1, den: 1 });
+        assert_eq!(play("2S 3H 4C 5D", "6C", None), Action::Stand);
+        assert_eq!(play("2S 3H 4C 5D", "6C", five), Action::Hit);
+        assert_eq!(play("2S 3H 4C 6D", "6C", five), Action::Stand);
+        assert_eq!(play("AS 2H 3C 2D", "6C", five), Action::Hit);
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cards::{card::Card, theme::card_rows}, util::term::{fit, set_redraw, terminal_size, tprint, visible_width}};
+
+/**
+ * A snapshot of the table during play, drawn to fill the terminal.
+ * The dealer sits at the top, seats are laid out side by side below, with a
+ * status bar above and an action bar below
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TableView {
+    pub status: String,         // balance, bets, and shoe info
+    pub dealer: SeatView,
+    pub seats: Vec<SeatView>,
+    pub actions: String,        // what can be done next
+}
+
+/**
+ * One hand at the table
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SeatView {
+    pub label: String,          // ex: `Hand 2  $25.00`
+    pub cards: Vec<Card>,
+    pub hidden: usize,          // face down cards after `cards`
+    pub note: String,           // shown below the cards. ex: the total
+    pub active: bool,           // whether this is the hand being played
+}
+
+// the view on screen, kept to redraw on resize
+static SHOWN: Mutex<Option<TableView>> = Mutex::new(None);
+
+// columns between seats
+const GAP: usize = 3;
+
+// narrowest a seat is drawn
+const MIN_SEAT: usize = 14;
+
+impl TableView {
+    /**
+     * Draw the view full screen, and keep it on screen through resizes
+     */
+    pub fn show(self) {
+        draw(&self);
+        *SHOWN.lock().unwrap() = Some(self);
+        set_redraw(Some(redraw));
+    }
+
+    /**
+     * Take the view off screen, clearing it
+     */
+    pub fn close() {
+        *SHOWN.lock().unwrap() = None;
+        set_redraw(None);
+        tprint!("[H[2J");
+    }
+
+    /**
+     * Lay the view out in `width` columns and `height` rows.
+     * Seats wrap onto more rows when they do not fit side by side. When the
+     * rows do not fit on screen, the rows around the active seat are kept.
+     * The cursor is left on the prompt of the action bar
+     */
+    pub fn render(&self, width: usize, height: usize) -> String {
+        // status bar, then the dealer
+        let mut top = vec![format!("[7m{}[0m", fit(&self.status, width)), String::new()];
+        top.extend(self.dealer.lines(width));
+        top.push(String::new());
+
+        // seats are as wide as the widest, and as many fit per row as can
+        let seat_width = self.seats.iter()
+            .flat_map(|seat| seat.lines(width))
+            .map(|line| visible_width(&line))
+            .max()
+            .unwrap_or(0)
+            .clamp(MIN_SEAT.min(width), width);
+        let per_row = ((width + GAP) / (seat_width + GAP)).max(1);
+
+        // join each row of seats line by line
+        let rows: Vec<Vec<String>> = self.seats.chunks(per_row).map(|row| {
+            let blocks: Vec<Vec<String>> = row.iter().map(|seat| seat.lines(seat_width)).collect();
+            let tallest = blocks.iter().map(|block| block.len()).max().unwrap_or(0);
+            let mut lines: Vec<String> = (0..tallest).map(|i| {
+                let parts: Vec<String> = blocks.iter()
+                    .map(|block| fit(block.get(i).map_or("", |line| line), seat_width))
+                    .collect();
+                parts.join(&" ".repeat(GAP))
+            }).collect();
+            lines.push(String::new());
+            lines
+        }).collect();
+
+        // action bar and prompt
+        let bottom = [fit(&self.actions, width)];
+
+        // keep as many rows as fit, starting early enough to show the active
+        // seat. rows left out are marked
+        let room = height.saturating_sub(top.len() + bottom.len() + 1);
+        let active = self.seats.iter().position(|seat| seat.active).unwrap_or(0) / per_row;
+        let (mut first, mut last) = (active.min(rows.len().saturating_sub(1)), active);
+        let used = |first: usize, last: usize| -> usize {
+            rows[first..=last.min(rows.len() - 1)].iter().map(|row| row.len()).sum::<usize>()
+                + usize::from(first > 0) + usize::from(last + 1 < rows.len())
+        };
+        if !rows.is_empty() {
+            while last + 1 < rows.len() && used(first, last + 1) <= room {
+                last += 1;
+            }
+            while first > 0 && used(first - 1, last) <= room {
+                first -= 1;
+            }
+        }
+
+        let mut body = vec![];
+        if first > 0 {
+            body.push(format!("({} more above)", first * per_row));
+        }
+        for row in rows.iter().take(last + 1).skip(first) {
+            body.extend(row.iter().cloned());
+        }
+        if last + 1 < rows.len() {
+            body.push(format!("({} more below)", self.seats.len() - (last + 1) * per_row));
+        }
+
+        // push the action bar to the bottom of the screen
+        let mut screen: Vec<String> = top.into_iter().chain(body).collect();
+        while screen.len() + bottom.len() + 1 < height {
+            screen.push(String::new());
+        }
+        screen.extend(bottom);
+
+        let lines: Vec<String> = screen.iter().map(|line| fit(line, width).trim_end().to_string()).collect();
+        format!("{}\n:: ", lines.join("\n"))
+    }
+}
+
+impl SeatView {
+    /**
+     * Lay the seat out as lines no wider than `width`: the label, the cards,
+     * then the note
+     */
+    fn lines(&self, width: usize) -> Vec<String> {
+        let mut lines = vec![match self.active {
+            true  => format!("[1m> {}[0m", self.label),
+            false => format!("  {}", self.label),
+        }];
+        lines.extend(card_rows(&self.cards, self.hidden, width.saturating_sub(2)).into_iter().map(|row| format!("  {}", row)));
+        if !self.note.is_empty() {
+            lines.push(format!("  {}", self.note));
+        }
+
+        lines
+    }
+}
+
+/**
+ * Draw a view over the whole terminal
+ */
+fn draw(view: &TableView) {
+    let (width, height) = terminal_size();
+    tprint!("[H[2J{}", view.render(width, height));
+}
+
+/**
+ * Draw the view on screen again, at the terminal's new size
+ */
+fn redraw() {
+    if let Some(view) = SHOWN.lock().unwrap().as_ref() {
+        draw(view);
+    }
+}
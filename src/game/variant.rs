@@ -0,0 +1,151 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/**
+ * Each game mode that can be played. Changes the deck, the options during
+ * play, and how hands are settled
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    Classic,
+    Spanish21,
+    Switch,
+    DoubleExposure,
+    FreeBet,
+    Pontoon,
+}
+
+impl Variant {
+    /**
+     * All game modes, in the order they are listed in settings
+     */
+    pub const ALL: [Variant; 6] = [Variant::Classic, Variant::Spanish21, Variant::Switch, Variant::DoubleExposure, Variant::FreeBet, Variant::Pontoon];
+
+    /**
+     * A short description of the mode's rules
+     */
+    pub fn description(&self) -> &'static str {
+        match self {
+            Variant::Classic => "Standard blackjack.",
+            Variant::Spanish21 => "No tens in the deck. Player 21 always wins, with bonuses for 5+ card 21s, 6-7-8, and 7-7-7. Late surrender and double down rescue.",
+            Variant::Switch => "Play two hands per seat and switch their second cards. Dealer 22 pushes, blackjack pays 1:1.",
+            Variant::DoubleExposure => "Both dealer cards are dealt face up. Dealer wins all ties except player blackjack, blackjack pays 1:1.",
+            Variant::FreeBet => "Free doubles on hard 9-11 and free splits of all pairs but tens. Dealer 22 pushes.",
+            Variant::Pontoon => "British rules. Both dealer cards hidden, twist below 15, buy cards for more stake. Five-card tricks and pontoons pay 2:1, dealer wins ties.",
+        }
+    }
+
+    /**
+     * The number of hands each seat plays. Each hand is bet the seat's bet
+     */
+    pub fn hands_per_seat(&self) -> usize {
+        match self {
+            Variant::Switch => 2,
+            _ => 1,
+        }
+    }
+
+    /**
+     * Returns whether a seat may switch the second cards of its hands after
+     * the deal
+     */
+    pub fn allows_switch(&self) -> bool {
+        matches!(self, Variant::Switch)
+    }
+
+    /**
+     * Returns whether a dealer bust of exactly 22 pushes every hand that is
+     * not a blackjack
+     */
+    pub fn pushes_on_dealer_22(&self) -> bool {
+        matches!(self, Variant::Switch | Variant::FreeBet)
+    }
+
+    /**
+     * Returns whether some doubles and splits are free, with the house
+     * putting up the extra stake
+     */
+    pub fn offers_free_bets(&self) -> bool {
+        matches!(self, Variant::FreeBet)
+    }
+
+    /**
+     * Returns whether the dealer's second card is dealt face up
+     */
+    pub fn shows_hole_card(&self) -> bool {
+        matches!(self, Variant::DoubleExposure)
+    }
+
+    /**
+     * Returns whether both dealer cards stay hidden until the dealer plays
+     */
+    pub fn hides_dealer_hand(&self) -> bool {
+        matches!(self, Variant::Pontoon)
+    }
+
+    /**
+     * Returns whether the game uses Pontoon terms and rankings: twist, stick,
+     * and buy, where a bought card does not end the hand, and hands stop at
+     * five cards
+     */
+    pub fn is_pontoon(&self) -> bool {
+        matches!(self, Variant::Pontoon)
+    }
+
+    /**
+     * The lowest total a player may stand on
+     */
+    pub fn min_stand(&self) -> u32 {
+        match self {
+            Variant::Pontoon => 15,
+            _ => 0,
+        }
+    }
+
+    /**
+     * The name of a two card 21
+     */
+    pub fn natural_name(&self) -> &'static str {
+        match self {
+            Variant::Pontoon => "pontoon",
+            _ => "blackjack",
+        }
+    }
+
+    /**
+     * Returns whether the ten number cards are taken out of the deck
+     */
+    pub fn removes_tens(&self) -> bool {
+        matches!(self, Variant::Spanish21)
+    }
+
+    /**
+     * Returns whether a two card hand may be surrendered for half its bet
+     */
+    pub fn allows_surrender(&self) -> bool {
+        matches!(self, Variant::Spanish21)
+    }
+
+    /**
+     * Returns whether a doubled hand may be surrendered for its doubling stake
+     */
+    pub fn allows_rescue(&self) -> bool {
+        matches!(self, Variant::Spanish21)
+    }
+}
+
+// make a variant printable
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Variant::Classic => "Classic",
+            Variant::Spanish21 => "Spanish 21",
+            Variant::Switch => "Blackjack Switch",
+            Variant::DoubleExposure => "Double Exposure",
+            Variant::FreeBet => "Free Bet",
+            Variant::Pontoon => "Pontoon",
+        })
+    }
+}
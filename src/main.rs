@@ -1,48 +1,148 @@
 mod game;
 mod cards;
 mod util;
+mod cli;
+mod net;
 
-use std::process::exit;
-use game::gamestate::GameState;
-use prompted::input;
+use std::{env, fs::File, io::BufReader, process::exit};
+use game::{config::{load_config, with_config}, gamestate::GameState};
 
-use crate::util::input::validated_input;
+use crate::{cards::{scenario::load_scenario, theme::set_suit_colors}, util::{backend::{set_backend, LineInput}, input::validated_input, keys::load_bindings, term::{self, tinput, tprint, tprintln}}};
 
 
 fn main() {
     // get new gamestate
     let mut input;
+    term::init_from_env();
+    term::watch_resize();
+
+    // read the command, if any. only those played at the keyboard wait at
+    // notices, the rest print them and go on
+    let args: Vec<String> = env::args().collect();
+    let command = match cli::parse(&args[1..]) {
+        Ok(command) => command,
+        Err(err)    => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            exit(2);
+        },
+    };
+    let interactive = command.as_ref().is_none_or(|command| command.is_interactive());
+
+    // read commands from a file, if given with `--input <file>`. otherwise
+    // commands are read by line when stdin is not a terminal
+    if let Some(pos) = args.iter().position(|arg| arg == "--input") {
+        let path = match args.get(pos + 1) {
+            Some(path) => path,
+            None       => {
+                eprintln!("--input needs a file");
+                exit(1);
+            },
+        };
+        match File::open(path) {
+            Ok(file) => set_backend(Box::new(LineInput::new(Box::new(BufReader::new(file))))),
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                exit(1);
+            },
+        }
+    }
 
     // attempt to load save file
     let mut gamestate = match GameState::load_state() {
         Ok(gs)  => gs,
+        Err(_) if interactive => {
+            tinput!("Failed to load from save file. generating a new one.\nEnter to continue...");
+            GameState::new()
+        },
         Err(_) => {
-            input!("Failed to load from save file. generating a new one.\nEnter to continue...");
+            eprintln!("Failed to load from save file. generating a new one.");
             GameState::new()
         },
     };
 
+    // load the config, keeping the defaults if it has errors
+    let errors = load_config();
+    if !errors.is_empty() && interactive {
+        tprintln!("Config:");
+        for error in errors.iter() {
+            tprintln!("  {}", error);
+        }
+        tinput!("Using the defaults. Enter to continue...");
+    } else if !errors.is_empty() {
+        eprintln!("Config:\n  {}\nUsing the defaults.", errors.join("\n  "));
+    }
+    with_config(|config| set_suit_colors(config.colors.standard, config.colors.four_color));
+
+    // load key bindings, warning of unreadable keys and conflicts. the
+    // config's keys are used over the key file
+    let warnings = load_bindings(with_config(|config| config.keys.clone()));
+    if !warnings.is_empty() && interactive {
+        tprintln!("Key bindings:");
+        for warning in warnings.iter() {
+            tprintln!("  {}", warning);
+        }
+        tinput!("Enter to continue...");
+    } else if !warnings.is_empty() {
+        eprintln!("Key bindings:\n  {}", warnings.join("\n  "));
+    }
+
+    // display flags override the saved settings, and any chosen later
+    gamestate.force_display(
+        args.iter().any(|arg| arg == "--screen-reader"),
+        args.iter().any(|arg| arg == "--four-color"),
+    );
+
+    // stack the shoe from a scenario file, if given with `--scenario <file>`
+    if let Some(pos) = args.iter().position(|arg| arg == "--scenario") {
+        let path = match args.get(pos + 1) {
+            Some(path) => path,
+            None       => {
+                eprintln!("--scenario needs a file");
+                exit(1);
+            },
+        };
+        match load_scenario(path) {
+            Ok(cards) => gamestate.set_scenario(cards),
+            Err(err)  => {
+                eprintln!("{}: {}", path, err);
+                exit(1);
+            },
+        }
+    }
+
+    // run the command given. `play` goes on to the menu when done, the
+    // rest exit
+    if let Some(command) = command {
+        let opens_menu = command.opens_menu();
+        if let Err(err) = cli::run(command, &mut gamestate) {
+            eprintln!("{}", err);
+            exit(1);
+        }
+        if !opens_menu {
+            exit(0);
+        }
+    }
+
     // simple input loop to play or exit
     loop {
         input = match gamestate.can_start() {
             true  => {
-                print!("\n[2J--Rust Blackjack--\n1. Play Game\n2. Play With Last Settings\n3. Bank History\n4. Exit\n:: ");
-                validated_input(|c| '1' <= c && c <= '4', |inp| 1 <= inp && inp <= 4)
+                tprint!("\n[2J--Rust Blackjack--\n1. Play Game\n2. Play With Last Settings\n3. Bank History\n4. Practice\n5. Exit\n:: ");
+                validated_input(|c| ('1'..='5').contains(&c), |inp| (1..=5).contains(&inp))
             },
             false => {
-                print!("\n[2J--Rust Blackjack--\n1. Play Game\n[2m2. Play With Last Settings[0m\n3. Bank History\n4. Exit\n:: ");
-                validated_input(|c| '1' <= c && c <= '4', |inp| 1 == inp || inp == 3 || inp == 4)
+                tprint!("\n[2J--Rust Blackjack--\n1. Play Game\n[2m2. Play With Last Settings[0m\n3. Bank History\n4. Practice\n5. Exit\n:: ");
+                validated_input(|c| ('1'..='5').contains(&c), |inp| inp != 2 && (1..=5).contains(&inp))
             },
         };
 
         match input {
-            1 => {
-                gamestate.new_settings();
-                gamestate.start_game();
-            },
+            1 if gamestate.choose_table() => gamestate.start_game(),
             2 => gamestate.start_game(),
             3 => gamestate.bank.run_ui(),
-            4 => exit(0),
+            // practice at the last table, money won or lost does not count
+            4 => gamestate.for_practice(gamestate.settings().cloned().unwrap_or_default()).start_game(),
+            5 => exit(0),
             _ => (),
         }
     }
@@ -0,0 +1,243 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cards::{card::Card, hand::HandTotal}, game::{bank::GameBank, chips::SeatBet, game::{Game, HandResult}, gamestate::min_bet, money::Money, player::{Player, Turn}, settings::{DealSpeed, GameSettings}}, util::{keys::Action, term::set_quiet}};
+
+use super::protocol::Connection;
+
+/**
+ * The version of the bot protocol, sent in `hello`
+ */
+const PROTOCOL: u32 = 1;
+
+/**
+ * Messages from the table to a bot, one per line as JSON tagged by `type`.
+ * A bot answers `bet` and `turn`, the rest need no answer. A `turn` shows
+ * every face up dealer card in `dealer`, both of them in Double Exposure, and
+ * the cards that make a Charlie in `charlie`, null without the rule. ex:
+ * `{"type":"turn","hand":0,"upcard":"KS","dealer":["KS"],"charlie":null,"cards":["9H","7C"],"total":{"best":16,...},...}`
+ */
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ToBot {
+    // sent once, before the first round
+    Hello { protocol: u32, variant: String, decks: usize, seats: usize, hands_per_seat: usize, min_bet: Money, balance: Money },
+    // answered with `FromBot::Bet`, a bet for each of up to `seats` seats
+    Bet { round: usize, balance: Money, seats: usize, min_bet: Money },
+    // answered with `FromBot::Action`, one of `options`
+    Turn { hand: usize, upcard: Option<Card>, dealer: Vec<Card>, charlie: Option<usize>, cards: Vec<Card>, total: HandTotal, hands: Vec<BotHand>, options: Vec<Action>, balance: Money },
+    Result { round: usize, dealer: Vec<Card>, hands: Vec<HandResult>, payout: Money, spent: Money, balance: Money },
+    // an answer that could not be used. the question is asked again
+    Error { text: String },
+    // sent once, after the last round
+    End { rounds: usize, balance: Money },
+}
+
+/**
+ * A hand at the table as a bot sees it. Hands not yet played show only
+ * their first card, as they would on screen
+ */
+#[derive(Serialize)]
+struct BotHand {
+    cards: Vec<Card>,
+    hidden: usize,
+    bet: Money,
+    doubled: bool,
+}
+
+/**
+ * Messages from a bot to the table
+ */
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FromBot {
+    Bet { bets: Vec<Money> },
+    Action { action: Action },
+    // stop before the next round
+    Quit,
+}
+
+/**
+ * Plays each hand by asking the bot
+ */
+struct BotPlayer {
+    conn: Rc<RefCell<Connection>>,
+}
+
+impl Player for BotPlayer {
+    fn play(&mut self, turn: &Turn) -> Action {
+        let hands = turn.hands.iter().enumerate().map(|(i, hand)| {
+            let shown = match i > turn.index {
+                true  => hand.cards().len().min(1),
+                false => hand.cards().len(),
+            };
+            BotHand {
+                cards: hand.cards()[..shown].to_vec(),
+                hidden: hand.cards().len() - shown,
+                bet: hand.bet(),
+                doubled: hand.is_doubled(),
+            }
+        }).collect();
+        let message = ToBot::Turn {
+            hand: turn.index,
+            upcard: turn.upcard.copied(),
+            dealer: turn.dealer.to_vec(),
+            charlie: turn.charlie.map(|rule| rule.cards),
+            cards: turn.hand.cards().to_vec(),
+            total: turn.hand.total(),
+            hands,
+            options: turn.options.iter().map(|(_, action)| *action).collect(),
+            balance: turn.available,
+        };
+
+        // ask until an offered play comes back. a bot that has gone stands
+        let mut conn = self.conn.borrow_mut();
+        if conn.send(&message).is_err() {
+            return turn.fallback();
+        }
+        loop {
+            match receive(&mut conn) {
+                Ok(FromBot::Action { action }) if turn.offers(action) => return action,
+                Ok(FromBot::Action { action }) => {
+                    let _ = conn.send(&ToBot::Error { text: format!("{:?} is not offered", action) });
+                },
+                Ok(_) => {
+                    let _ = conn.send(&ToBot::Error { text: String::from("expected an action") });
+                },
+                Err(_) => return turn.fallback(),
+            }
+        }
+    }
+}
+
+/**
+ * Read the next message from the bot, telling it about any message that
+ * could not be read and waiting for another.
+ * Returns an error when the bot has gone
+ */
+fn receive(conn: &mut Connection) -> io::Result<FromBot> {
+    loop {
+        match conn.receive(None) {
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                conn.send(&ToBot::Error { text: format!("could not read message: {}", err) })?;
+            },
+            result => return result,
+        }
+    }
+}
+
+/**
+ * Let a bot play `rounds` rounds at a table with `settings`, betting from a
+ * bank of its own that starts with `bankroll`. The bot is run with
+ * `command`, or talked to over stdin and stdout when None.
+ * Stops early if the bot quits or cannot cover the minimum bet.
+ * Returns the rounds played and the final balance, or an error if the bot
+ * could not be run or went away
+ */
+pub fn run_bot(settings: &GameSettings, rounds: usize, bankroll: Money, command: Option<&str>) -> io::Result<(usize, Money)> {
+    let (conn, mut child) = match command {
+        Some(command) => {
+            let (conn, child) = Connection::spawn(command)?;
+            (conn, Some(child))
+        },
+        None => (Connection::stdio(), None),
+    };
+
+    // nothing else may be printed while stdout carries the protocol
+    set_quiet(true);
+    let result = play_rounds(settings, rounds, bankroll, conn);
+    set_quiet(false);
+
+    // let the bot finish on its own
+    if let Some(child) = child.as_mut() {
+        drop(child.stdin.take());
+        child.wait()?;
+    }
+    result
+}
+
+/**
+ * Play rounds against the bot on `conn`. See `run_bot`
+ */
+fn play_rounds(settings: &GameSettings, rounds: usize, bankroll: Money, conn: Connection) -> io::Result<(usize, Money)> {
+    let mut settings = settings.clone();
+    settings.speed = DealSpeed::Instant;
+    settings.side_bets = vec![];
+    let max_seats = settings.hand_count;
+    let per_seat = settings.variant.hands_per_seat();
+
+    let conn = Rc::new(RefCell::new(conn));
+    let mut bank = GameBank::with_balance(bankroll);
+    let mut game = Game::new(&settings);
+    game.set_player(Box::new(BotPlayer { conn: conn.clone() }));
+
+    conn.borrow_mut().send(&ToBot::Hello {
+        protocol: PROTOCOL,
+        variant: settings.variant.to_string(),
+        decks: settings.deck_count,
+        seats: max_seats,
+        hands_per_seat: per_seat,
+        min_bet: min_bet(),
+        balance: bank.get_balance(),
+    })?;
+
+    let mut played = 0;
+    while played < rounds && bank.get_balance() >= min_bet() * per_seat {
+        // ask for bets until they can be placed
+        let balance = bank.get_balance();
+        let seats = max_seats.min(balance.count_of(min_bet() * per_seat));
+        conn.borrow_mut().send(&ToBot::Bet { round: played + 1, balance, seats, min_bet: min_bet() })?;
+        let placed = loop {
+            let mut conn = conn.borrow_mut();
+            let bets = match receive(&mut conn)? {
+                FromBot::Bet { bets } => bets,
+                FromBot::Quit => break None,
+                FromBot::Action { .. } => {
+                    conn.send(&ToBot::Error { text: String::from("expected a bet") })?;
+                    continue;
+                },
+            };
+            // the total is checked, as bets too large to add up are as
+            // invalid as bets too large to pay
+            match (bets.len(), Money::checked_total(&bets, per_seat)) {
+                (0, _) => conn.send(&ToBot::Error { text: String::from("bet on at least one seat") })?,
+                (n, _) if n > seats => conn.send(&ToBot::Error { text: format!("bet on at most {} seats", seats) })?,
+                _ if bets.iter().any(|bet| *bet < min_bet()) => conn.send(&ToBot::Error { text: format!("each bet must be at least {}", min_bet()) })?,
+                _ if bets.iter().any(|bet| *bet > balance) => conn.send(&ToBot::Error { text: format!("each bet must be at most the balance of {}", balance) })?,
+                (_, Some(cost)) if cost <= balance => break Some(bets),
+                (_, Some(cost)) => conn.send(&ToBot::Error { text: format!("bets cost {}, more than the balance of {}", cost, balance) })?,
+                (_, None) => conn.send(&ToBot::Error { text: format!("bets cost more than the balance of {}", balance) })?,
+            }
+        };
+        let Some(placed) = placed else {
+            break;
+        };
+
+        // play with a seat for each bet
+        bank.buy(placed.iter().copied().sum::<Money>() * per_seat, placed.len() * per_seat);
+        let bets: Vec<SeatBet> = placed.iter().map(|bet| SeatBet { main: *bet, side: vec![] }).collect();
+        settings.hand_count = bets.len();
+        game.update_settings(&settings);
+        let result = game.play(&bets, bank.get_balance());
+        if result.bought > 0 {
+            bank.buy(result.spent, result.bought);
+        }
+        if !result.payout.is_zero() {
+            bank.win(result.payout);
+        }
+        played += 1;
+
+        conn.borrow_mut().send(&ToBot::Result {
+            round: played,
+            dealer: result.dealer,
+            hands: result.hands,
+            payout: result.payout,
+            spent: result.spent,
+            balance: bank.get_balance(),
+        })?;
+    }
+
+    conn.borrow_mut().send(&ToBot::End { rounds: played, balance: bank.get_balance() })?;
+    Ok((played, bank.get_balance()))
+}
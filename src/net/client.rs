@@ -0,0 +1,138 @@
+use std::{io, net::TcpStream};
+
+use crate::{cards::theme::format_cards, game::{chips::{run_betting_ui, SeatBet}, table::{SeatView, TableView}}, util::{input::{read_one_char, validated_input}, keys::{action_for, hint, Action, Context}, term::{screen_reader, tinput, tprint, tprintln}}};
+
+use super::protocol::{ClientMessage, Connection, ServerMessage};
+
+/**
+ * Sit at the table hosted on `address` as `name`, asking for `seats` seats.
+ * Bets and plays are read as at a local table. Runs until the player leaves
+ * from the chip tray.
+ * Returns an error if the connection fails
+ */
+pub fn join(address: &str, name: &str, seats: usize) -> io::Result<()> {
+    let mut conn = Connection::tcp(TcpStream::connect(address)?)?;
+    conn.send(&ClientMessage::Join { name: name.to_string(), seats })?;
+    tprintln!("Connected to {}, waiting for a seat...", address);
+
+    let mut last_bets: Vec<SeatBet> = vec![];
+    let mut last_action = None;
+    let mut showing = false;
+    loop {
+        let message = match conn.receive(None) {
+            Ok(message) => message,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                close(&mut showing);
+                tprintln!("The server closed the table.");
+                return Ok(());
+            },
+            Err(err) => return Err(err),
+        };
+
+        match message {
+            ServerMessage::Welcome { player, seats, table, balance } => {
+                tprintln!("Seated as player {} with {} seats at {}.\nYou have {}.", player, seats, table, balance);
+            },
+            ServerMessage::Bet { balance, seats, hands_per_seat, min_bet } => {
+                close(&mut showing);
+                match run_betting_ui(balance, seats, hands_per_seat, min_bet, &[], &last_bets) {
+                    Some(bets) => {
+                        conn.send(&ClientMessage::Bet { bets: bets.iter().map(|bet| bet.main).collect() })?;
+                        last_bets = bets;
+                        tprintln!("\n[2JWaiting for the other players to bet...");
+                    },
+                    None => {
+                        conn.send(&ClientMessage::Leave)?;
+                        return Ok(());
+                    },
+                }
+            },
+            ServerMessage::Turn { hand, table, options } => {
+                let action = read_play(hand, table, &options, last_action, &mut showing);
+                last_action = Some(action);
+                conn.send(&ClientMessage::Action { action })?;
+            },
+            ServerMessage::Table { mut table, text } => match screen_reader() {
+                true  => tprintln!("{}.", text),
+                false => {
+                    table.actions = text;
+                    table.show();
+                    showing = true;
+                },
+            },
+            ServerMessage::Result { dealer, hands, payout, spent, balance } => {
+                close(&mut showing);
+                tprintln!("\n[2JDealer: {}\n", format_cards(&dealer, 0));
+                for hand in hands.iter() {
+                    tprintln!("Hand {}: {} ; ({}) [{}]", hand.seat + 1, format_cards(&hand.cards, 0), hand.total, hand.result);
+                }
+                match spent.is_zero() {
+                    true  => tprintln!("\nPaid {}", payout),
+                    false => tprintln!("\nPaid {}, after buying {} more", payout, spent),
+                }
+                tinput!("You now have {}\n\nEnter to continue...", balance);
+            },
+            ServerMessage::Info { text } => tprintln!("{}", text),
+            ServerMessage::Error { text } => tprintln!("Server: {}", text),
+        }
+    }
+}
+
+/**
+ * Take the table off screen, if it is on screen
+ */
+fn close(showing: &mut bool) {
+    if *showing {
+        TableView::close();
+        *showing = false;
+    }
+}
+
+/**
+ * Show `table` with hand `n` to play, and read what to do with it from the
+ * `options` offered, as at a local table.
+ * Enter alone repeats `last` if it is still an option
+ */
+fn read_play(n: usize, mut table: TableView, options: &[(String, Action)], last: Option<Action>, showing: &mut bool) -> Action {
+    let labels: Vec<String> = options.iter().map(|(label, action)| hint(label, *action)).collect();
+    let offered = |c: char| action_for(Context::Play, c).filter(|action| options.iter().any(|(_, offered)| offered == action));
+    let repeat = last.filter(|action| options.iter().any(|(_, offered)| offered == action));
+
+    loop {
+        let input = match screen_reader() {
+            true => {
+                tprintln!("\n{}\n\nOptions: {}", describe_seat(&table.dealer), labels.join(", "));
+                for seat in table.seats.iter() {
+                    tprintln!("{}", describe_seat(seat));
+                }
+                tprint!("Playing hand {}\n:: ", n + 1);
+                match validated_input(|c| offered(c).is_some(), |s: String| s.len() <= 1) {
+                    input if input.is_empty() => repeat,
+                    input => input.chars().next().and_then(offered),
+                }
+            },
+            false => {
+                table.actions = format!("Hand {}: {}   (enter repeats)", n + 1, labels.join(", "));
+                table.clone().show();
+                *showing = true;
+                match read_one_char() {
+                    '\n' => repeat,
+                    c => offered(c),
+                }
+            },
+        };
+        if let Some(action) = input {
+            return action;
+        }
+    }
+}
+
+/**
+ * Describe a seat as a line for screen readers
+ */
+fn describe_seat(seat: &SeatView) -> String {
+    match seat.note.is_empty() {
+        true  => format!("{}: {}.", seat.label, format_cards(&seat.cards, seat.hidden)),
+        false => format!("{}: {}, {}.", seat.label, format_cards(&seat.cards, seat.hidden), seat.note),
+    }
+}
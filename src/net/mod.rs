@@ -0,0 +1,4 @@
+pub mod protocol;
+pub mod server;
+pub mod client;
+pub mod bot;
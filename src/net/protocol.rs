@@ -0,0 +1,145 @@
+use std::{io::{self, BufRead, BufReader, Write}, net::TcpStream, process::{Child, Command, Stdio}, time::Duration};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{cards::card::Card, game::{game::HandResult, money::Money, table::TableView}, util::keys::Action};
+
+/**
+ * Messages from the table to a player. Sent one per line as JSON, tagged by
+ * `type`. ex: `{"type":"info","text":"Waiting for players"}`
+ */
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    // seated at the table
+    Welcome { player: usize, seats: usize, table: String, balance: Money },
+    // place a bet on each seat, answered with `ClientMessage::Bet`
+    Bet { balance: Money, seats: usize, hands_per_seat: usize, min_bet: Money },
+    // play hand `hand`, answered with `ClientMessage::Action`
+    Turn { hand: usize, table: TableView, options: Vec<(String, Action)> },
+    // the table while someone else plays
+    Table { table: TableView, text: String },
+    // how the player's hands finished
+    Result { dealer: Vec<Card>, hands: Vec<HandResult>, payout: Money, spent: Money, balance: Money },
+    Info { text: String },
+    Error { text: String },
+}
+
+/**
+ * Messages from a player to the table, sent the same way
+ */
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // take up to `seats` seats
+    Join { name: String, seats: usize },
+    // the main bet on each seat. no bets sits the round out
+    Bet { bets: Vec<Money> },
+    Action { action: Action },
+    Leave,
+}
+
+/**
+ * One end of a conversation in JSON lines
+ */
+pub struct Connection {
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+    stream: Option<TcpStream>,  // to set timeouts on, when over TCP
+    partial: String,            // a line cut off by a timeout
+}
+
+impl Connection {
+    /**
+     * Talk over a TCP stream
+     */
+    pub fn tcp(stream: TcpStream) -> io::Result<Connection> {
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            reader: Box::new(BufReader::new(stream.try_clone()?)),
+            writer: Box::new(stream.try_clone()?),
+            stream: Some(stream),
+            partial: String::new(),
+        })
+    }
+
+    /**
+     * Talk over stdin and stdout
+     */
+    pub fn stdio() -> Connection {
+        Connection {
+            reader: Box::new(BufReader::new(io::stdin())),
+            writer: Box::new(io::stdout()),
+            stream: None,
+            partial: String::new(),
+        }
+    }
+
+    /**
+     * Run `command` with the shell and talk over its stdin and stdout. Its
+     * stderr is passed through.
+     * Returns the connection and the running process
+     */
+    pub fn spawn(command: &str) -> io::Result<(Connection, Child)> {
+        let mut child = Command::new("sh")
+            .args(["-c", command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(io::Error::other("could not open the process's stdin and stdout"));
+        };
+        let conn = Connection {
+            reader: Box::new(BufReader::new(stdout)),
+            writer: Box::new(stdin),
+            stream: None,
+            partial: String::new(),
+        };
+        Ok((conn, child))
+    }
+
+    /**
+     * Send `message` as one line
+     */
+    pub fn send<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let line = serde_json::to_string(message).map_err(io::Error::other)?;
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+
+    /**
+     * Wait up to `timeout` for the next message, or forever if None. Blank
+     * lines are skipped.
+     * Returns an error of kind `WouldBlock` or `TimedOut` on a timeout,
+     * `UnexpectedEof` when the other end has gone, or `InvalidData` for a
+     * message that could not be read
+     */
+    pub fn receive<T: DeserializeOwned>(&mut self, timeout: Option<Duration>) -> io::Result<T> {
+        if let Some(stream) = &self.stream {
+            stream.set_read_timeout(timeout)?;
+        }
+
+        loop {
+            // a timeout keeps what was read of the line for next time
+            if self.reader.read_line(&mut self.partial)? == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"));
+            }
+            if !self.partial.ends_with('\n') {
+                continue;
+            }
+
+            let line = std::mem::take(&mut self.partial);
+            if line.trim().is_empty() {
+                continue;
+            }
+            return serde_json::from_str(line.trim()).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+    }
+}
+
+/**
+ * Returns whether `err` is a read timing out
+ */
+pub fn timed_out(err: &io::Error) -> bool {
+    matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
@@ -0,0 +1,303 @@
+use std::{cell::RefCell, io, net::{TcpListener, TcpStream}, rc::Rc, sync::mpsc::{self, Receiver}, thread, time::Duration};
+
+use crate::{game::{bank::GameBank, chips::SeatBet, config::with_config, game::Game, gamestate::min_bet, money::Money, player::{Player, Turn}, settings::{DealSpeed, GameSettings}}, util::{keys::Action, term::set_quiet}};
+
+use super::protocol::{timed_out, ClientMessage, Connection, ServerMessage};
+
+/**
+ * Where the server listens unless told otherwise
+ */
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+// how long a player has to join, to bet, and to play a hand
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+const BET_TIMEOUT: Duration = Duration::from_secs(120);
+const TURN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/**
+ * A player at the table, with a bank of their own
+ */
+struct Seated {
+    id: usize,              // shown to everyone, counting up from 1
+    name: String,
+    conn: Connection,
+    bank: GameBank,
+    seats: usize,           // seats taken at the table
+    gone: bool,             // left or lost the connection. removed between rounds
+}
+
+/**
+ * Plays each hand by asking the player at its seat over the network. The
+ * others are shown the table while they wait
+ */
+struct Remote {
+    players: Rc<RefCell<Vec<Seated>>>,
+}
+
+impl Seated {
+    /**
+     * Send `message`, marking the player gone if it could not be sent
+     */
+    fn send(&mut self, message: &ServerMessage) {
+        if !self.gone && self.conn.send(message).is_err() {
+            self.gone = true;
+        }
+    }
+
+    /**
+     * Wait up to `timeout` for the next message. A player who left or lost
+     * the connection is marked gone.
+     * Returns None on a timeout or when gone
+     */
+    fn receive(&mut self, timeout: Duration) -> Option<ClientMessage> {
+        if self.gone {
+            return None;
+        }
+        match self.conn.receive(Some(timeout)) {
+            Ok(ClientMessage::Leave) => {
+                self.gone = true;
+                None
+            },
+            Ok(message) => Some(message),
+            Err(err) if timed_out(&err) => None,
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                self.send(&ServerMessage::Error { text: format!("could not read message: {}", err) });
+                None
+            },
+            Err(_) => {
+                self.gone = true;
+                None
+            },
+        }
+    }
+}
+
+impl Player for Remote {
+    fn play(&mut self, turn: &Turn) -> Action {
+        let mut players = self.players.borrow_mut();
+
+        // everyone else watches the hand being played
+        let text = format!("{} is playing hand {}", players[turn.owner].name, turn.index + 1);
+        for (i, other) in players.iter_mut().enumerate() {
+            if i != turn.owner {
+                other.send(&ServerMessage::Table { table: turn.table.clone(), text: text.clone() });
+            }
+        }
+
+        // ask until an offered play comes back. stand for a player who is
+        // gone or too slow
+        let player = &mut players[turn.owner];
+        let options = turn.options.iter().map(|(label, action)| (label.to_string(), *action)).collect();
+        player.send(&ServerMessage::Turn { hand: turn.index, table: turn.table.clone(), options });
+        loop {
+            match player.receive(TURN_TIMEOUT) {
+                Some(ClientMessage::Action { action }) if turn.offers(action) => return action,
+                Some(ClientMessage::Action { action }) => {
+                    player.send(&ServerMessage::Error { text: format!("{:?} is not offered", action) });
+                },
+                // a late answer from betting
+                Some(_) => (),
+                None => {
+                    player.send(&ServerMessage::Info { text: String::from("Out of time, standing") });
+                    return turn.fallback();
+                },
+            }
+        }
+    }
+}
+
+/**
+ * Host a table on `address` with `settings`, where each player may take up
+ * to `settings.hand_count` seats. Runs until stopped.
+ * Returns an error if the address cannot be listened on
+ */
+pub fn run_server(address: &str, settings: &GameSettings) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!("Hosting {} with {} decks on {}. Ctrl-C to stop.", settings.variant, settings.deck_count, listener.local_addr()?);
+
+    // connections are accepted in the background and seated between rounds
+    let (sender, joining) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if sender.send(stream).is_err() {
+                return;
+            }
+        }
+    });
+
+    // the server plays no hands itself, so nothing is drawn
+    set_quiet(true);
+    let mut settings = settings.clone();
+    settings.speed = DealSpeed::Instant;
+    settings.side_bets = vec![];
+    let max_seats = settings.hand_count;
+
+    let players = Rc::new(RefCell::new(Vec::new()));
+    let mut game = Game::new(&settings);
+    game.set_player(Box::new(Remote { players: players.clone() }));
+
+    let mut next_id = 1;
+    let mut round = 1;
+    loop {
+        seat_joining(&joining, &mut players.borrow_mut(), &settings, max_seats, &mut next_id);
+        if play_round(&mut game, &mut settings, &players, round) {
+            round += 1;
+        }
+
+        // let everyone know who left
+        let mut players = players.borrow_mut();
+        let left: Vec<String> = players.iter().filter(|player| player.gone).map(|player| player.name.clone()).collect();
+        players.retain(|player| !player.gone);
+        for name in left {
+            println!("{} left", name);
+            for player in players.iter_mut() {
+                player.send(&ServerMessage::Info { text: format!("{} left the table", name) });
+            }
+        }
+    }
+}
+
+/**
+ * Seat everyone waiting to join, while seats are free. Waits for someone
+ * when the table is empty
+ */
+fn seat_joining(joining: &Receiver<TcpStream>, players: &mut Vec<Seated>, settings: &GameSettings, max_seats: usize, next_id: &mut usize) {
+    let mut streams: Vec<TcpStream> = joining.try_iter().collect();
+    if players.is_empty() && streams.is_empty() {
+        println!("Waiting for players...");
+        match joining.recv() {
+            Ok(stream) => streams.push(stream),
+            Err(_) => return,
+        }
+    }
+
+    let table_seats = with_config(|config| config.table.max_hands);
+    for stream in streams {
+        let Ok(mut conn) = Connection::tcp(stream) else {
+            continue;
+        };
+        let (name, wanted) = match conn.receive(Some(JOIN_TIMEOUT)) {
+            Ok(ClientMessage::Join { name, seats }) => (name, seats),
+            _ => continue,
+        };
+
+        // take what seats are free, up to the most each player may have
+        let taken: usize = players.iter().map(|player| player.seats).sum();
+        let seats = wanted.clamp(1, max_seats).min(table_seats - taken);
+        if seats == 0 {
+            let _ = conn.send(&ServerMessage::Error { text: format!("The table is full, all {} seats are taken", table_seats) });
+            continue;
+        }
+
+        let name = match name.trim() {
+            ""   => format!("Player {}", next_id),
+            name => name.to_string(),
+        };
+        let mut player = Seated { id: *next_id, name, conn, bank: GameBank::new(), seats, gone: false };
+        *next_id += 1;
+        println!("{} joined with {} seats", player.name, seats);
+
+        let table = format!("{} with {} decks, {} min bet", settings.variant, settings.deck_count, min_bet());
+        player.send(&ServerMessage::Welcome { player: player.id, seats, table, balance: player.bank.get_balance() });
+        for other in players.iter_mut() {
+            other.send(&ServerMessage::Info { text: format!("{} joined the table", player.name) });
+        }
+        players.push(player);
+    }
+}
+
+/**
+ * Take bets from everyone, play a round with each player's seats, and pay
+ * each player from their own bank.
+ * Returns whether a round was played, false if no one bet
+ */
+fn play_round(game: &mut Game, settings: &mut GameSettings, players: &Rc<RefCell<Vec<Seated>>>, round: usize) -> bool {
+    let per_seat = settings.variant.hands_per_seat();
+
+    // ask everyone for bets, then collect them, so players bet at once
+    let mut all = players.borrow_mut();
+    for player in all.iter_mut() {
+        if player.bank.get_balance() < min_bet() * per_seat {
+            player.bank.reset_balance();
+            player.send(&ServerMessage::Info { text: format!("You ran out of money. Your balance was reset to {}", player.bank.get_balance()) });
+        }
+        let seats = player.seats.min(player.bank.get_balance().count_of(min_bet() * per_seat));
+        player.send(&ServerMessage::Bet { balance: player.bank.get_balance(), seats, hands_per_seat: per_seat, min_bet: min_bet() });
+    }
+
+    // seat each valid bet, paid for from the player's bank. `owners` maps
+    // each seat to its player
+    let mut bets = vec![];
+    let mut owners = vec![];
+    for (i, player) in all.iter_mut().enumerate() {
+        let placed = loop {
+            match player.receive(BET_TIMEOUT) {
+                Some(ClientMessage::Bet { bets }) => break bets,
+                // a late answer from the last round
+                Some(ClientMessage::Action { .. }) => (),
+                Some(_) => break vec![],
+                None => {
+                    player.send(&ServerMessage::Info { text: String::from("Out of time, sitting this round out") });
+                    break vec![];
+                },
+            }
+        };
+
+        // each bet is checked before they are totalled, and a total too
+        // large to count is as invalid as one too large to pay
+        let balance = player.bank.get_balance();
+        let cost = match placed.iter().all(|bet| *bet >= min_bet() && *bet <= balance) {
+            true  => Money::checked_total(&placed, per_seat).filter(|cost| *cost <= balance),
+            false => None,
+        };
+        let cost = match cost {
+            Some(cost) if placed.len() <= player.seats => cost,
+            _ => {
+                player.send(&ServerMessage::Error { text: format!("Bets must be at least {} on at most {} seats, costing at most {}", min_bet(), player.seats, balance) });
+                continue;
+            },
+        };
+        if !placed.is_empty() {
+            player.bank.buy(cost, placed.len() * per_seat);
+        }
+        for bet in placed {
+            bets.push(SeatBet { main: bet, side: vec![] });
+            owners.push(i);
+        }
+    }
+    if bets.is_empty() {
+        return false;
+    }
+    let wallets: Vec<Money> = all.iter().map(|player| player.bank.get_balance()).collect();
+    drop(all);
+
+    // play with a seat for each bet
+    settings.hand_count = bets.len();
+    game.update_settings(settings);
+    let result = game.play_table(&bets, &wallets, &owners);
+    println!("Round {}: {} seats, dealer drew {}", round, bets.len(), result.dealer.iter().map(|card| card.code()).collect::<Vec<_>>().join(" "));
+
+    // pay each player for their seats
+    let mut all = players.borrow_mut();
+    for (i, player) in all.iter_mut().enumerate() {
+        let seats: Vec<usize> = (0..owners.len()).filter(|seat| owners[*seat] == i).collect();
+        if seats.is_empty() {
+            continue;
+        }
+        let payout: Money = seats.iter().map(|seat| result.seats[*seat].payout).sum();
+        let spent: Money = seats.iter().map(|seat| result.seats[*seat].spent).sum();
+        let bought: usize = seats.iter().map(|seat| result.seats[*seat].bought).sum();
+        if bought > 0 {
+            player.bank.buy(spent, bought);
+        }
+        if !payout.is_zero() {
+            player.bank.win(payout);
+        }
+
+        let hands = result.hands.iter().filter(|hand| owners[hand.seat] == i).cloned().collect();
+        println!("  {}: paid {}, now has {}", player.name, payout, player.bank.get_balance());
+        player.send(&ServerMessage::Result { dealer: result.dealer.clone(), hands, payout, spent, balance: player.bank.get_balance() });
+    }
+
+    true
+}
@@ -0,0 +1,243 @@
+use termios::{Termios, TCSANOW, ECHO, ICANON, tcsetattr};
+use super::term::redraw_if_resized;
+use std::{io::{self, BufRead, BufReader}, os::fd::AsRawFd, sync::Mutex};
+
+/**
+ * Where keys and lines of input come from.
+ * Reads return `Err` once input runs out
+ */
+pub trait InputBackend: Send {
+    /**
+     * Read one key
+     */
+    fn read_key(&mut self) -> io::Result<Key>;
+
+    /**
+     * Read one line, without its line ending
+     */
+    fn read_line(&mut self) -> io::Result<String>;
+
+    /**
+     * Returns whether keys are read as they are pressed, so input can be
+     * drawn as it is typed
+     */
+    fn is_interactive(&self) -> bool;
+}
+
+/**
+ * A key pressed. Keys that send escape sequences, like the arrows, are read
+ * as one key
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Delete,
+    Escape,
+    // a key with no use here, like F1
+    Unknown,
+}
+
+/**
+ * Keys straight from the terminal as they are pressed, for interactive play
+ */
+pub struct RawTerminal;
+
+/**
+ * Commands read one per line from a pipe or file, so whole sessions can be
+ * scripted. A key is the first character of its line, and an empty line is
+ * enter. Each command is echoed, so the output reads as a transcript
+ */
+pub struct LineInput {
+    reader: Box<dyn BufRead + Send>,
+}
+
+// the backend every read goes through. picked on first use if not set
+static BACKEND: Mutex<Option<Box<dyn InputBackend>>> = Mutex::new(None);
+
+/**
+ * Set the backend every read goes through
+ */
+pub fn set_backend(backend: Box<dyn InputBackend>) {
+    *BACKEND.lock().unwrap() = Some(backend);
+}
+
+/**
+ * Pick a backend for stdin: the raw terminal if it is a terminal, otherwise
+ * commands by line
+ */
+pub fn detect() -> Box<dyn InputBackend> {
+    // SAFETY: only checks the file descriptor
+    match unsafe { libc::isatty(io::stdin().as_raw_fd()) } == 1 {
+        true  => Box::new(RawTerminal),
+        false => Box::new(LineInput::new(Box::new(BufReader::new(io::stdin())))),
+    }
+}
+
+/**
+ * Run `f` with the backend in use, picking one if none is set
+ */
+pub fn with_backend<T>(f: impl FnOnce(&mut dyn InputBackend) -> T) -> T {
+    let mut backend = BACKEND.lock().unwrap();
+    f(backend.get_or_insert_with(detect).as_mut())
+}
+
+impl InputBackend for RawTerminal {
+    /**
+     * Read one key without waiting for enter.
+     * The full screen view is redrawn if the terminal is resized while waiting.
+     * Taken from https://stackoverflow.com/questions/26321592/how-can-i-read-one-character-from-stdin-without-having-to-hit-enter
+     */
+    fn read_key(&mut self) -> io::Result<Key> {
+        // get stdin file descriptor
+        let stdin = io::stdin().as_raw_fd();
+
+        // get current terminal 'settings' to restore to later
+        let termios = Termios::from_fd(stdin)?;
+        let mut new_termios = termios;
+
+        // set the flags to *just* canonical and echo mode
+        new_termios.c_lflag &= !(ICANON | ECHO);
+        tcsetattr(stdin, TCSANOW, &new_termios)?;
+
+        // read exactly 1 key, then put the settings back. the rest of an
+        // escape sequence is read while the terminal is still raw
+        let key = match read_byte(stdin) {
+            Ok(0x1b) => read_escape(stdin),
+            Ok(byte) => Ok(Key::Char(byte as char)),
+            Err(e)   => Err(e),
+        };
+        tcsetattr(stdin, TCSANOW, &termios)?;
+
+        key
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line)? {
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => Ok(trim_line_ending(line)),
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+impl LineInput {
+    /**
+     * Read commands from `reader`
+     */
+    pub fn new(reader: Box<dyn BufRead + Send>) -> LineInput {
+        LineInput { reader }
+    }
+}
+
+impl InputBackend for LineInput {
+    fn read_key(&mut self) -> io::Result<Key> {
+        let line = self.read_line()?;
+        Ok(Key::Char(line.chars().next().unwrap_or('\n')))
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line)? {
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => {
+                let line = trim_line_ending(line);
+                println!("{line}");
+                Ok(line)
+            },
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+/**
+ * Drop the `\n` or `\r\n` ending a line
+ */
+fn trim_line_ending(mut line: String) -> String {
+    while line.ends_with(['\n', '\r']) {
+        line.pop();
+    }
+    line
+}
+
+/**
+ * Read one byte straight from `fd`, skipping the buffer of `io::stdin` so no
+ * more than one key is taken.
+ * A resize interrupts the read; the screen is redrawn and the read tried again
+ */
+fn read_byte(fd: i32) -> io::Result<u8> {
+    let mut byte = 0u8;
+    loop {
+        // SAFETY: reads at most one byte into `byte`
+        let read = unsafe { libc::read(fd, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+        match read {
+            1 => return Ok(byte),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+                redraw_if_resized();
+            },
+        }
+    }
+}
+
+/**
+ * Read the rest of a key that starts with escape, ex: `ESC [ D` for left.
+ * Escape on its own is the escape key; the bytes of a sequence arrive
+ * together, so a short wait tells them apart
+ */
+fn read_escape(fd: i32) -> io::Result<Key> {
+    if !pending(fd, 30) {
+        return Ok(Key::Escape);
+    }
+
+    // only CSI `ESC [` and SS3 `ESC O` sequences are keys here
+    match read_byte(fd)? {
+        b'[' | b'O' => (),
+        _ => return Ok(Key::Unknown),
+    }
+
+    // parameters run until the final byte
+    let mut params = String::new();
+    let last = loop {
+        match read_byte(fd)? {
+            b @ 0x40..=0x7e => break b,
+            b => params.push(b as char),
+        }
+    };
+
+    Ok(match (last, params.as_str()) {
+        (b'A', _) => Key::Up,
+        (b'B', _) => Key::Down,
+        (b'C', _) => Key::Right,
+        (b'D', _) => Key::Left,
+        (b'H', _) | (b'~', "1" | "7") => Key::Home,
+        (b'F', _) | (b'~', "4" | "8") => Key::End,
+        (b'~', "3") => Key::Delete,
+        _ => Key::Unknown,
+    })
+}
+
+/**
+ * Returns whether a byte can be read from `fd` within `ms` milliseconds
+ */
+fn pending(fd: i32, ms: i32) -> bool {
+    let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    // SAFETY: polls exactly the one descriptor given
+    unsafe { libc::poll(&mut poll, 1, ms) > 0 }
+}
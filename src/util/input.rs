@@ -1,47 +1,102 @@
-use termios::{Termios, TCSANOW, ECHO, ICANON, tcsetattr};
-use std::{any::Any, fmt::Debug, io::{self, Read, Write}, os::fd::AsRawFd, str::FromStr};
+use super::{backend::{with_backend, Key}, keys::menu_digit, term::{screen_reader, tprint, tprintln}};
+use std::{any::Any, fmt::{Debug, Display}, io::{self, Write}, process::exit, str::FromStr};
 
+/**
+ * The answer to a question that can be backed out of
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Field<T> {
+    Value(T),   // the answer given
+    Back,       // go back to the question before
+    Cancel,     // leave the questions altogether
+}
+
+impl<T> Field<T> {
+    /**
+     * Change the answer given, keeping `Back` and `Cancel` as they are
+     */
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Field<U> {
+        match self {
+            Field::Value(v) => Field::Value(f(v)),
+            Field::Back     => Field::Back,
+            Field::Cancel   => Field::Cancel,
+        }
+    }
+}
+
+impl Field<()> {
+    /**
+     * Pass over a question that does not apply, in the direction the
+     * questions are moving
+     */
+    pub fn skip(forward: bool) -> Field<()> {
+        match forward {
+            true  => Field::Value(()),
+            false => Field::Back,
+        }
+    }
+}
 
 /**
- * Reads one character input from stdin, and calls the callback function with
- * the read character.
- * If the callback returns true, it will read another character.
- * If the callback returns false, the function returns.
- * Taken from https://stackoverflow.com/questions/26321592/how-can-i-read-one-character-from-stdin-without-having-to-hit-enter
+ * Reads one key from the input backend.
+ * From a terminal, the key is taken as soon as it is pressed.
+ * Exits once input runs out
+ */
+pub fn read_key() -> Key {
+    // flush stdout and read exactly 1 key
+    io::stdout().lock().flush().unwrap();
+    let (key, interactive) = with_backend(|backend| (backend.read_key(), backend.is_interactive()));
+    let key = key.unwrap_or_else(|_| end_of_input());
+
+    // screen readers hear the key that was pressed
+    if let Key::Char(c) = key && screen_reader() && interactive && !c.is_control() {
+        println!("{c}");
+    }
+    key
+}
+
+/**
+ * Reads one character input from the input backend.
+ * Escape is read as `\x1b`, and other keys without a character, like the
+ * arrows, are skipped.
+ * Exits once input runs out
  */
 pub fn read_one_char() -> char {
-    // get stdin file descriptor
-    let stdin = io::stdin().as_raw_fd();
-
-    // get current terminal 'settings' to restore to later
-    let termios = Termios::from_fd(stdin).unwrap();
-    let mut new_termios = termios.clone();
-    
-    // set the flags to *just* canonical and echo mode
-    new_termios.c_lflag &= !(ICANON | ECHO);
-    tcsetattr(stdin, TCSANOW, &mut new_termios).unwrap();
-
-    // get stdout and stdin
-    let stdout = io::stdout();
-    let mut reader = io::stdin();
-
-    // buffer to read 1 byte
-    let mut buffer = [0;1];
-
-    // flush stdout and read exactly 1 character from stdin
-    stdout.lock().flush().unwrap();
-    reader.read_exact(&mut buffer).unwrap();
-
-    // put the settings back, return the read character
-    tcsetattr(stdin, TCSANOW, & termios).unwrap();
-    buffer[0] as char
+    loop {
+        match read_key() {
+            Key::Char(c) => return c,
+            Key::Escape  => return '\x1b',
+            _ => (),
+        }
+    }
+}
+
+/**
+ * Reads one full line from the input backend, without its line ending.
+ * Exits once input runs out
+ */
+pub fn read_line() -> String {
+    io::stdout().lock().flush().unwrap();
+    with_backend(|backend| backend.read_line()).unwrap_or_else(|_| end_of_input())
+}
+
+/**
+ * Stop the game when there is no more input, as at the end of a script
+ */
+fn end_of_input() -> ! {
+    tprintln!("\nInput ended.");
+    exit(0)
 }
 
 /**
  * Get an input, validated under multiple criteria.
  * -> character_validator: valid character conditional.
  * -> input_validator: valid input conditional.
- * Highlights valid inputs green and invalid outputs red.
+ * Highlights valid inputs green and invalid outputs red. The input can be
+ * edited with left, right, home, end, delete, and ctrl-u.
+ * Keys rebound to menu options are read as the option's digit.
+ * In screen reader mode, or when input is not from a terminal, reads a
+ * plain line instead.
  * Returns the input once valid and enter is pressed
  */
 pub fn validated_input<ValidChar, ValidInput, Ret: FromStr + 'static>
@@ -50,69 +105,248 @@ pub fn validated_input<ValidChar, ValidInput, Ret: FromStr + 'static>
         ValidChar: Fn(char) -> bool,
         ValidInput: Fn(Ret) -> bool {
 
-    /**
-     * Parses an input, then validates it.
-     * If the input cannot be parsed, returns false.
-     * If the input can be parsed, returns the result of the validator on the 
-     * input
-     */
-    fn validate_parse_or_false<V, T: Any + FromStr>(validate: &V, input: &String) -> bool where V: Fn(T) -> bool {
-        match input.parse() {
-            Ok(inp) => validate(inp),
-            Err(_)    => false,
+    match edit_input(&valid_character, &valid_input, None, false) {
+        Field::Value(inp) => inp,
+        _ => unreachable!("validated_input cannot be backed out of"),
+    }
+}
+
+/**
+ * Get an answer to one of a series of questions, validated like
+ * `validated_input`.
+ * Enter on an empty input keeps `default`, if it is still valid. Up goes back
+ * to the question before, and escape cancels. Lines read `back` and `cancel`
+ * for the same
+ */
+pub fn field_input<ValidChar, ValidInput, Ret: FromStr + Display + 'static>
+        (valid_character: ValidChar, valid_input: ValidInput, default: Option<Ret>) -> Field<Ret>
+        where <Ret as FromStr>::Err:Debug,
+        ValidChar: Fn(char) -> bool,
+        ValidInput: Fn(Ret) -> bool {
+
+    let default = default.map(|d| d.to_string());
+    edit_input(&valid_character, &valid_input, default, true)
+}
+
+/**
+ * Get a yes or no answer to one of a series of questions, with a single key.
+ * Enter keeps `default`. Up goes back to the question before, and escape
+ * cancels
+ */
+pub fn yes_no_field(default: Option<bool>) -> Field<bool> {
+    // screen readers and scripts answer with a line
+    if screen_reader() || !with_backend(|backend| backend.is_interactive()) {
+        if let Some(d) = default {
+            tprint!("(enter for {}) ", if d { "y" } else { "n" });
+        }
+        loop {
+            match (read_line().trim().to_lowercase().as_str(), default) {
+                ("y" | "yes", _) => return Field::Value(true),
+                ("n" | "no", _)  => return Field::Value(false),
+                ("", Some(d))    => return Field::Value(d),
+                ("back", _)      => return Field::Back,
+                ("cancel", _)    => return Field::Cancel,
+                _ => println!("Invalid input, try again."),
+            }
         }
     }
 
-    // start with empty input
-    let mut input = String::new();
-    
-    // keep taking input until input is valid
     loop {
-        // keep taking input until backspace is pressed
+        match (read_key(), default) {
+            (Key::Char('y'), _) => return Field::Value(true),
+            (Key::Char('n'), _) => return Field::Value(false),
+            (Key::Char('\n'), Some(d)) => return Field::Value(d),
+            (Key::Up, _)     => return Field::Back,
+            (Key::Escape, _) => return Field::Cancel,
+            _ => (),
+        }
+    }
+}
+
+/**
+ * How to get around a series of questions, to show above them
+ */
+pub fn field_hint() -> &'static str {
+    match screen_reader() || !with_backend(|backend| backend.is_interactive()) {
+        true  => "Enter keeps the last answer. Type back for the previous question, or cancel to stop.\n",
+        false => "[2mEnter keeps the dimmed answer. Up for the previous question, escape to cancel.[0m\n",
+    }
+}
+
+/**
+ * Step through `count` questions, asking each with `ask`. An answer moves on
+ * to the next question and `Back` to the one before. `ask` is told whether
+ * the questions are moving forward, so a question that does not apply can
+ * be passed over in either direction.
+ * Returns `Back` when backing out of the first question, and `Cancel` once
+ * cancelled
+ */
+pub fn step_fields(count: usize, mut ask: impl FnMut(usize, bool) -> Field<()>) -> Field<()> {
+    let mut step = 0;
+    let mut forward = true;
+    while step < count {
+        match ask(step, forward) {
+            Field::Value(()) => {
+                step += 1;
+                forward = true;
+            },
+            Field::Back if step == 0 => return Field::Back,
+            Field::Back => {
+                step -= 1;
+                forward = false;
+            },
+            Field::Cancel => return Field::Cancel,
+        }
+    }
+
+    Field::Value(())
+}
+
+/**
+ * Parses an input, then validates it.
+ * If the input cannot be parsed, returns false.
+ * If the input can be parsed, returns the result of the validator on the 
+ * input
+ */
+fn validate_parse_or_false<V, T: Any + FromStr>(validate: &V, input: &str) -> bool where V: Fn(T) -> bool {
+    match input.parse() {
+        Ok(inp) => validate(inp),
+        Err(_)    => false,
+    }
+}
+
+/**
+ * Edit an input until it is valid and enter is pressed, as in
+ * `validated_input`. Backing out is only possible with `can_leave`
+ */
+fn edit_input<ValidChar, ValidInput, Ret: FromStr + 'static>
+        (valid_character: &ValidChar, valid_input: &ValidInput, default: Option<String>, can_leave: bool) -> Field<Ret>
+        where <Ret as FromStr>::Err:Debug,
+        ValidChar: Fn(char) -> bool,
+        ValidInput: Fn(Ret) -> bool {
+
+    // a default that is no longer allowed is not offered
+    let default = default.filter(|d| validate_parse_or_false(valid_input, d));
+
+    // screen readers and scripts get a plain line of input, with a sentence
+    // when it is not valid
+    if screen_reader() || !with_backend(|backend| backend.is_interactive()) {
+        if let Some(d) = &default {
+            tprint!("(enter for {}) ", d);
+        }
         loop {
-            // read one character, and check what was recieved
-            match read_one_char() as char {
-                // if a valid character
-                c if valid_character(c) => {
-                    // add it to the input.
-                    input.push(c);
-
-                    // move left enough times to properly print the input later
-                    let len = input.len();
-                    if len > 1 {
-                        print!("[{}D", len-1);
-                    }
-                } 
-                // if the backspace character
-                '\x7f' => {
-                    // remove last char from the input
-                    let p = input.pop();
-
-                    // move left enough times to properly print the input later
-                    let len = input.len();
-                    if p.is_some() {
-                        print!("[{}D", len+1);
-                    }
-                },
-                // if pressed enter, check the input
-                '\n' => break,
-                // ignore all other input
-                _ => continue,
+            let line = read_line();
+            let line = line.trim();
+            match (line.to_lowercase().as_str(), &default) {
+                ("", Some(d)) => return Field::Value(d.parse().unwrap()),
+                ("back", _) if can_leave   => return Field::Back,
+                ("cancel", _) if can_leave => return Field::Cancel,
+                _ => (),
             }
 
-            // color according to validity
-            if validate_parse_or_false(&valid_input, &input) {
-                print!("[38;5;40m{input}[K[0m");
-            } else {
-                print!("[38;5;196m{input}[K[0m");
+            let line: String = line.chars()
+                .map(|c| match valid_character(c) {
+                    true  => c,
+                    false => menu_digit(c).unwrap_or(c),
+                })
+                .collect();
+            let line = line.as_str();
+            if line.chars().all(valid_character) && validate_parse_or_false(valid_input, line) {
+                return Field::Value(line.parse().unwrap());
             }
+            println!("Invalid input, try again.");
         }
+    }
 
-        if validate_parse_or_false(&valid_input, &input) {
-            break;
+    // start with empty input, showing the default in its place
+    let mut input: Vec<char> = vec![];
+    let mut cursor: usize = 0;
+    draw_input(&input, 0, 0, default.as_deref(), false);
+
+    // keep taking keys until enter is pressed on a valid input
+    loop {
+        let before = cursor;
+        match read_key() {
+            // if pressed enter, check the input. an empty input takes the
+            // default
+            Key::Char('\n') => {
+                let text: String = input.iter().collect();
+                match &default {
+                    Some(d) if text.is_empty() => return Field::Value(d.parse().unwrap()),
+                    _ if validate_parse_or_false(valid_input, &text) => return Field::Value(text.parse().unwrap()),
+                    _ => continue,
+                }
+            },
+            // leave the question
+            Key::Up if can_leave     => return Field::Back,
+            Key::Escape if can_leave => return Field::Cancel,
+            // move the cursor
+            Key::Left  => cursor = cursor.saturating_sub(1),
+            Key::Right => cursor = (cursor + 1).min(input.len()),
+            Key::Home | Key::Char('\x01') => cursor = 0,
+            Key::End  | Key::Char('\x05') => cursor = input.len(),
+            // ctrl-u clears everything before the cursor
+            Key::Char('\x15') => {
+                input.drain(..cursor);
+                cursor = 0;
+            },
+            // backspace removes the character before the cursor, delete the
+            // one under it
+            Key::Char('\x7f' | '\x08') if cursor > 0 => {
+                cursor -= 1;
+                input.remove(cursor);
+            },
+            Key::Delete if cursor < input.len() => {
+                input.remove(cursor);
+            },
+            // add valid characters at the cursor. keys bound to menu options
+            // stand in for their digit
+            Key::Char(c) => {
+                let c = match valid_character(c) {
+                    true  => c,
+                    false => menu_digit(c).unwrap_or(c),
+                };
+                if !valid_character(c) {
+                    continue;
+                }
+                input.insert(cursor, c);
+                cursor += 1;
+            },
+            // ignore all other input
+            _ => continue,
         }
+
+        // color according to validity
+        let text: String = input.iter().collect();
+        draw_input(&input, before, cursor, default.as_deref(), validate_parse_or_false(valid_input, &text));
     }
+}
 
-    // return the parsed input
-    input.parse().unwrap()
+/**
+ * Draw `input` over itself, with the cursor at `cursor`. `before` is where
+ * the cursor was. An empty input shows `default` dimmed in its place
+ */
+fn draw_input(input: &[char], before: usize, cursor: usize, default: Option<&str>, valid: bool) {
+    // back to the start of the input
+    if before > 0 {
+        tprint!("[{}D", before);
+    }
+
+    let (text, shown) = match (input.is_empty(), default) {
+        (true, Some(d)) => (format!("[2m{d}[0m"), d.len()),
+        (true, None)    => (String::new(), 0),
+        (false, _)      => {
+            let text: String = input.iter().collect();
+            match valid {
+                true  => (format!("[38;5;40m{text}[0m"), input.len()),
+                false => (format!("[38;5;196m{text}[0m"), input.len()),
+            }
+        },
+    };
+    tprint!("{text}[K");
+
+    // then back to the cursor
+    if shown > cursor {
+        tprint!("[{}D", shown - cursor);
+    }
 }
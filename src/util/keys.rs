@@ -0,0 +1,462 @@
+use std::{collections::HashMap, fmt::Display, fs, io, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/**
+ * Everything a key can be bound to
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    // playing a hand. pontoon twists, sticks, and buys with the first three
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+    // practice tables: see how each play would have gone. `Undo` takes back
+    // the last play
+    WhatIf,
+    // blackjack switch: swap the second cards of a seat's hands
+    SwitchCards,
+    // the chip tray
+    Chip1,
+    Chip2,
+    Chip3,
+    Chip4,
+    Chip5,
+    Chip6,
+    RemoveChip,
+    ClearSpot,
+    Undo,
+    SwitchSpot,
+    PlaceBets,
+    // the chip tray and bank history
+    Next,
+    Previous,
+    Help,
+    Quit,
+    // bank history
+    Stats,
+    // numbered menu options
+    Choice0,
+    Choice1,
+    Choice2,
+    Choice3,
+    Choice4,
+    Choice5,
+    Choice6,
+    Choice7,
+    Choice8,
+    Choice9,
+}
+
+/**
+ * Where keys are read. A key may only do one thing in each context
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Context {
+    Play,
+    Bet,
+    History,
+    Menu,
+}
+
+/**
+ * A starting set of bindings
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyPreset {
+    // the original keys. ex: `h` hits, `j`/`k` move between seats
+    #[default]
+    Default,
+    // play on the home row with `hjkl`, `?` for help
+    Vim,
+    // everything reachable from a number pad
+    Numpad,
+}
+
+/**
+ * The keys bound to each action
+ */
+#[derive(Clone)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Vec<char>>,
+}
+
+/**
+ * Bindings as written in the key config: a preset, with the keys of some
+ * actions replaced. Keys are single characters, or `enter` and `space`
+ */
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyConfig {
+    #[serde(default)]
+    pub preset: KeyPreset,
+    #[serde(default, alias = "bindings")]
+    pub keys: HashMap<Action, Vec<String>>,
+}
+
+// where key bindings are read from
+pub const KEYS_PATH: &str = "keys.json";
+
+// the bindings in use. the default preset until set
+static BINDINGS: Mutex<Option<KeyBindings>> = Mutex::new(None);
+
+impl Action {
+    /**
+     * All actions, in the order they are listed in help
+     */
+    pub const ALL: [Action; 33] = [
+        Action::Hit, Action::Stand, Action::Double, Action::Split, Action::Surrender, Action::WhatIf, Action::SwitchCards,
+        Action::Chip1, Action::Chip2, Action::Chip3, Action::Chip4, Action::Chip5, Action::Chip6,
+        Action::RemoveChip, Action::ClearSpot, Action::Undo, Action::SwitchSpot, Action::PlaceBets,
+        Action::Next, Action::Previous, Action::Help, Action::Quit, Action::Stats,
+        Action::Choice0, Action::Choice1, Action::Choice2, Action::Choice3, Action::Choice4,
+        Action::Choice5, Action::Choice6, Action::Choice7, Action::Choice8, Action::Choice9,
+    ];
+
+    /**
+     * The contexts the action is read in
+     */
+    pub fn contexts(&self) -> &'static [Context] {
+        match self {
+            Action::Hit | Action::Stand | Action::Double | Action::Split | Action::Surrender | Action::WhatIf | Action::SwitchCards => &[Context::Play],
+            Action::Undo => &[Context::Bet, Context::Play],
+            Action::Next | Action::Previous | Action::Help | Action::Quit => &[Context::Bet, Context::History],
+            Action::Stats => &[Context::History],
+            Action::Choice0 | Action::Choice1 | Action::Choice2 | Action::Choice3 | Action::Choice4
+                | Action::Choice5 | Action::Choice6 | Action::Choice7 | Action::Choice8 | Action::Choice9 => &[Context::Menu],
+            _ => &[Context::Bet],
+        }
+    }
+
+    /**
+     * What the action does in `context`, for help screens
+     */
+    pub fn describe(&self, context: Context) -> &'static str {
+        match (self, context) {
+            (Action::Chip1, _) => "add a $1 chip",
+            (Action::Chip2, _) => "add a $5 chip",
+            (Action::Chip3, _) => "add a $25 chip",
+            (Action::Chip4, _) => "add a $100 chip",
+            (Action::Chip5, _) => "add a $500 chip",
+            (Action::Chip6, _) => "add a $1000 chip",
+            (Action::RemoveChip, _) => "remove the top chip",
+            (Action::ClearSpot, _) => "clear the spot",
+            (Action::Undo, Context::Play) => "undo last play (practice)",
+            (Action::Undo, _) => "undo last chip",
+            (Action::SwitchSpot, _) => "switch between main and side bets",
+            (Action::PlaceBets, _) => "place bets",
+            (Action::Next, Context::History) => "scroll down",
+            (Action::Next, _) => "next seat",
+            (Action::Previous, Context::History) => "scroll up",
+            (Action::Previous, _) => "previous seat",
+            (Action::Help, _) => "help",
+            (Action::Quit, Context::History) => "quit",
+            (Action::Quit, _) => "cancel",
+            (Action::Stats, _) => "stats",
+            (Action::Hit, _) => "hit",
+            (Action::Stand, _) => "stand",
+            (Action::Double, _) => "double",
+            (Action::Split, _) => "split",
+            (Action::Surrender, _) => "surrender",
+            (Action::WhatIf, _) => "what if (practice)",
+            (Action::SwitchCards, _) => "switch second cards (Blackjack Switch)",
+            _ => "menu option",
+        }
+    }
+
+    /**
+     * The digit of a menu option action
+     */
+    fn choice_digit(&self) -> Option<char> {
+        let ndx = Action::ALL.iter().position(|action| action == self)?;
+        let first = Action::ALL.iter().position(|action| *action == Action::Choice0)?;
+        ndx.checked_sub(first).and_then(|digit| char::from_digit(digit as u32, 10))
+    }
+}
+
+impl KeyPreset {
+    /**
+     * Build the bindings of the preset
+     */
+    pub fn bindings(&self) -> KeyBindings {
+        // keys shared by every preset: chips and menus stay on the digits
+        let mut keys: HashMap<Action, Vec<char>> = HashMap::new();
+        for (action, key) in [Action::Chip1, Action::Chip2, Action::Chip3, Action::Chip4, Action::Chip5, Action::Chip6].iter().zip('1'..='6') {
+            keys.insert(*action, vec![key]);
+        }
+        for action in Action::ALL {
+            if let Some(digit) = action.choice_digit() {
+                keys.insert(action, vec![digit]);
+            }
+        }
+        keys.insert(Action::PlaceBets, vec!['\n']);
+
+        let preset: &[(Action, &[char])] = match self {
+            KeyPreset::Default => &[
+                (Action::Hit, &['h', 't']), (Action::Stand, &['s']), (Action::Double, &['d', 'b']),
+                (Action::Split, &['l']), (Action::Surrender, &['r']), (Action::WhatIf, &['w']), (Action::SwitchCards, &['y']),
+                (Action::RemoveChip, &['x']), (Action::ClearSpot, &['c']), (Action::Undo, &['u']),
+                (Action::SwitchSpot, &['s']), (Action::Next, &['j']), (Action::Previous, &['k']),
+                (Action::Help, &['h']), (Action::Quit, &['q']), (Action::Stats, &['s']),
+            ],
+            KeyPreset::Vim => &[
+                (Action::Hit, &['j']), (Action::Stand, &['k']), (Action::Double, &['l']),
+                (Action::Split, &['h']), (Action::Surrender, &['x']), (Action::WhatIf, &['?']), (Action::SwitchCards, &['y']),
+                (Action::RemoveChip, &['x']), (Action::ClearSpot, &['d']), (Action::Undo, &['u']),
+                (Action::SwitchSpot, &['l', 'h']), (Action::Next, &['j']), (Action::Previous, &['k']),
+                (Action::Help, &['?']), (Action::Quit, &['q']), (Action::Stats, &['s']),
+            ],
+            KeyPreset::Numpad => &[
+                (Action::Hit, &['+']), (Action::Stand, &['-']), (Action::Double, &['*']),
+                (Action::Split, &['/']), (Action::Surrender, &['.']), (Action::WhatIf, &['9']), (Action::SwitchCards, &['1']),
+                (Action::RemoveChip, &['/']), (Action::ClearSpot, &['.']), (Action::Undo, &['0']),
+                (Action::SwitchSpot, &['*']), (Action::Next, &['+']), (Action::Previous, &['-']),
+                (Action::Help, &['7']), (Action::Quit, &['8']), (Action::Stats, &['5']),
+            ],
+        };
+        for (action, bound) in preset {
+            keys.insert(*action, bound.to_vec());
+        }
+
+        KeyBindings { keys }
+    }
+}
+
+impl KeyBindings {
+    /**
+     * Get the keys bound to `action`
+     */
+    pub fn keys(&self, action: Action) -> &[char] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    /**
+     * Find the action `key` is bound to in `context`. Letters match either case
+     */
+    pub fn action(&self, context: Context, key: char) -> Option<Action> {
+        Action::ALL.iter()
+            .filter(|action| action.contexts().contains(&context))
+            .find(|action| self.keys(**action).iter().any(|bound| bound.eq_ignore_ascii_case(&key)))
+            .copied()
+    }
+
+    /**
+     * Find every key bound to more than one action in the same context.
+     * Returns a warning for each
+     */
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        for context in [Context::Play, Context::Bet, Context::History, Context::Menu] {
+            // group the actions of the context by key
+            let mut by_key: Vec<(char, Vec<Action>)> = vec![];
+            for action in Action::ALL.iter().filter(|action| action.contexts().contains(&context)) {
+                for key in self.keys(*action) {
+                    let key = key.to_ascii_lowercase();
+                    match by_key.iter_mut().find(|(bound, _)| *bound == key) {
+                        Some((_, actions)) => actions.push(*action),
+                        None => by_key.push((key, vec![*action])),
+                    }
+                }
+            }
+
+            for (key, actions) in by_key.iter().filter(|(_, actions)| actions.len() > 1) {
+                let names: Vec<&str> = actions.iter().map(|action| action.describe(context)).collect();
+                warnings.push(format!("{} is bound to {} while {}", key_name(*key), names.join(" and "), context));
+            }
+        }
+
+        warnings
+    }
+}
+
+impl KeyConfig {
+    /**
+     * Build the bindings of the config, with a warning for each key that
+     * could not be read
+     */
+    pub fn bindings(&self) -> (KeyBindings, Vec<String>) {
+        let mut bindings = self.preset.bindings();
+        let mut warnings = vec![];
+
+        for (action, keys) in self.keys.iter() {
+            let mut bound = vec![];
+            for key in keys {
+                match parse_key(key) {
+                    Some(c) => bound.push(c),
+                    None => warnings.push(format!("`{}` is not a key, for {:?}", key, action)),
+                }
+            }
+            bindings.keys.insert(*action, bound);
+        }
+
+        (bindings, warnings)
+    }
+}
+
+/**
+ * Use the key bindings of `config`, or load them from `KEYS_PATH` if there
+ * are none and it exists.
+ * Returns warnings for unreadable keys and for conflicts
+ */
+pub fn load_bindings(config: Option<KeyConfig>) -> Vec<String> {
+    let config: KeyConfig = match config {
+        Some(config) => config,
+        None => match fs::read_to_string(KEYS_PATH) {
+            Ok(text) => match serde_json::from_str(&text) {
+                Ok(config) => config,
+                Err(err) => return vec![format!("{}: {}", KEYS_PATH, err)],
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => KeyConfig::default(),
+            Err(err) => return vec![format!("{}: {}", KEYS_PATH, err)],
+        },
+    };
+
+    let (bindings, mut warnings) = config.bindings();
+    warnings.extend(bindings.conflicts());
+    set_bindings(bindings);
+    warnings
+}
+
+/**
+ * Use `bindings` for every key read
+ */
+pub fn set_bindings(bindings: KeyBindings) {
+    *BINDINGS.lock().unwrap() = Some(bindings);
+}
+
+/**
+ * Run `f` with the bindings in use
+ */
+fn with_bindings<T>(f: impl FnOnce(&KeyBindings) -> T) -> T {
+    let mut bindings = BINDINGS.lock().unwrap();
+    f(bindings.get_or_insert_with(|| KeyPreset::Default.bindings()))
+}
+
+/**
+ * Find the action `key` is bound to in `context`
+ */
+pub fn action_for(context: Context, key: char) -> Option<Action> {
+    with_bindings(|bindings| bindings.action(context, key))
+}
+
+/**
+ * Get the digit of the menu option `key` is bound to, if any
+ */
+pub fn menu_digit(key: char) -> Option<char> {
+    action_for(Context::Menu, key).and_then(|action| action.choice_digit())
+}
+
+/**
+ * Show `label` with the key for `action` picked out, ex: `sp[L]it`.
+ * The first bound key found in the label is bracketed; if none are, the
+ * first key is put in front, ex: `[J] Hit`
+ */
+pub fn hint(label: &str, action: Action) -> String {
+    with_bindings(|bindings| {
+        let keys = bindings.keys(action);
+        for key in keys {
+            if let Some((pos, c)) = label.char_indices().find(|(_, c)| c.eq_ignore_ascii_case(key)) {
+                return format!("{}[{}]{}", &label[..pos], c.to_ascii_uppercase(), &label[pos + c.len_utf8()..]);
+            }
+        }
+
+        // capitalise the label after the key
+        let mut chars = label.chars();
+        let label = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+        match keys.first() {
+            Some(key) => format!("[{}] {}", key_name(key.to_ascii_uppercase()), label),
+            None => label,
+        }
+    })
+}
+
+/**
+ * List the keys of every action in `context`, one per line, for help screens.
+ * ex: `\tj - next seat`
+ */
+pub fn help(context: Context) -> String {
+    with_bindings(|bindings| {
+        let lines: Vec<String> = Action::ALL.iter()
+            .filter(|action| action.contexts().contains(&context))
+            .filter(|action| !bindings.keys(**action).is_empty())
+            .map(|action| {
+                let keys: Vec<String> = bindings.keys(*action).iter().map(|key| key_name(*key)).collect();
+                format!("\t{} - {}", keys.join("/"), action.describe(context))
+            })
+            .collect();
+        lines.join("\n")
+    })
+}
+
+/**
+ * Get the first key bound to `action`, as shown on screen. ex: `h`, `Enter`
+ */
+pub fn key_label(action: Action) -> String {
+    with_bindings(|bindings| match bindings.keys(action).first() {
+        Some(key) => key_name(*key),
+        None => String::from("unbound"),
+    })
+}
+
+/**
+ * Read a key as written in the key config
+ */
+fn parse_key(key: &str) -> Option<char> {
+    let mut chars = key.chars();
+    match (key.to_lowercase().as_str(), chars.next(), chars.next()) {
+        ("enter", _, _) => Some('\n'),
+        ("space", _, _) => Some(' '),
+        (_, Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/**
+ * Name a key as shown on screen
+ */
+fn key_name(key: char) -> String {
+    match key {
+        '\n' => String::from("Enter"),
+        ' ' => String::from("Space"),
+        c => c.to_string(),
+    }
+}
+
+// make a context printable, as in `while betting`
+impl Display for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Context::Play => "playing",
+            Context::Bet => "betting",
+            Context::History => "in history",
+            Context::Menu => "in menus",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        for preset in [KeyPreset::Default, KeyPreset::Vim, KeyPreset::Numpad] {
+            assert_eq!(preset.bindings().conflicts(), Vec::<String>::new(), "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn numpad_preset_stays_on_the_numpad() {
+        let bindings = KeyPreset::Numpad.bindings();
+        for action in Action::ALL {
+            for key in bindings.keys(action) {
+                assert!("0123456789+-*/.\n".contains(*key), "{:?} is bound to {:?}", action, key);
+            }
+        }
+    }
+}
@@ -1,2 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod util;
 pub mod input;
+pub mod backend;
+pub mod keys;
+pub mod term;
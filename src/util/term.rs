@@ -0,0 +1,257 @@
+use std::{env, sync::{atomic::{AtomicBool, Ordering}, Mutex}};
+
+// whether colors may be shown. turned off by `NO_COLOR`
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+// whether cards use the colorblind-friendly four-color deck
+static FOUR_COLOR: AtomicBool = AtomicBool::new(false);
+
+// whether output is linear, for screen readers
+static SCREEN_READER: AtomicBool = AtomicBool::new(false);
+
+// whether output is dropped, for simulations
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+// whether the terminal was resized since the last redraw
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+// draws the full screen view again, if one is shown
+static REDRAW: Mutex<Option<fn()>> = Mutex::new(None);
+
+/**
+ * Read display options from the environment.
+ * Colors are turned off when `NO_COLOR` is set to anything but an empty
+ * string, see https://no-color.org
+ */
+pub fn init_from_env() {
+    if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        COLOR.store(false, Ordering::Relaxed);
+    }
+}
+
+/**
+ * Returns whether colors are shown. Screen reader mode never shows colors
+ */
+pub fn color_enabled() -> bool {
+    COLOR.load(Ordering::Relaxed) && !screen_reader()
+}
+
+/**
+ * Set whether cards use the four-color deck
+ */
+pub fn set_four_color(on: bool) {
+    FOUR_COLOR.store(on, Ordering::Relaxed);
+}
+
+/**
+ * Returns whether cards use the four-color deck
+ */
+pub fn four_color() -> bool {
+    FOUR_COLOR.load(Ordering::Relaxed)
+}
+
+/**
+ * Set whether output is linear, for screen readers
+ */
+pub fn set_screen_reader(on: bool) {
+    SCREEN_READER.store(on, Ordering::Relaxed);
+}
+
+/**
+ * Returns whether output is linear, for screen readers.
+ * Screen clears and cursor moves are skipped, and cards are read as words
+ */
+pub fn screen_reader() -> bool {
+    SCREEN_READER.load(Ordering::Relaxed)
+}
+
+/**
+ * Set whether output printed through `tprint!` and `tprintln!` is dropped
+ */
+pub fn set_quiet(on: bool) {
+    QUIET.store(on, Ordering::Relaxed);
+}
+
+/**
+ * Returns whether output is dropped
+ */
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/**
+ * Get the size of the terminal as columns and rows.
+ * Falls back to 80x24 when stdout is not a terminal
+ */
+pub fn terminal_size() -> (usize, usize) {
+    // SAFETY: TIOCGWINSZ only writes a `winsize` into `size`
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let found = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+
+    match found && size.ws_col > 0 && size.ws_row > 0 {
+        true  => (size.ws_col as usize, size.ws_row as usize),
+        false => (80, 24),
+    }
+}
+
+/**
+ * Watch for the terminal being resized.
+ * The handler is installed without `SA_RESTART`, so a read waiting on a key
+ * wakes up and can redraw the screen
+ */
+pub fn watch_resize() {
+    extern "C" fn on_resize(_: libc::c_int) {
+        RESIZED.store(true, Ordering::Relaxed);
+    }
+
+    // SAFETY: the handler only stores to an atomic
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_resize as *const () as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut());
+    }
+}
+
+/**
+ * Set what draws the full screen view again after a resize.
+ * `None` when no full screen view is shown
+ */
+pub fn set_redraw(redraw: Option<fn()>) {
+    *REDRAW.lock().unwrap() = redraw;
+}
+
+/**
+ * Draw the full screen view again if the terminal was resized since the
+ * last call
+ */
+pub fn redraw_if_resized() {
+    if !RESIZED.swap(false, Ordering::Relaxed) {
+        return;
+    }
+
+    let redraw = *REDRAW.lock().unwrap();
+    if let Some(redraw) = redraw {
+        redraw();
+    }
+}
+
+/**
+ * Count the columns text takes on screen, skipping escape sequences
+ */
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            // sequences end on their final letter
+            (true, c) if c.is_ascii_alphabetic() => in_escape = false,
+            (true, _) => (),
+        }
+    }
+
+    width
+}
+
+/**
+ * Cut text down to `width` columns, keeping escape sequences.
+ * Pads with spaces when shorter, so columns line up
+ */
+pub fn fit(text: &str, width: usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut shown = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) if shown == width => continue,
+            (false, _) => shown += 1,
+            (true, c) if c.is_ascii_alphabetic() => in_escape = false,
+            (true, _) => (),
+        }
+        out.push(c);
+    }
+
+    out.push_str(&" ".repeat(width - shown));
+    out
+}
+
+/**
+ * Filter escape sequences out of text by the display options.
+ * Colors are removed when colors are off, and every other escape sequence
+ * is removed in screen reader mode
+ */
+pub fn filter(text: &str) -> String {
+    let (color, linear) = (color_enabled(), screen_reader());
+    if color && !linear {
+        return String::from(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' || chars.peek() != Some(&'[') {
+            out.push(c);
+            continue;
+        }
+
+        // read the whole sequence, up to its final letter
+        let mut seq = String::from("\x1b[");
+        chars.next();
+        for c in chars.by_ref() {
+            seq.push(c);
+            if c.is_ascii_alphabetic() {
+                break;
+            }
+        }
+
+        // keep what the options allow
+        let is_color = seq.ends_with('m');
+        if (is_color && color) || (!is_color && !linear) {
+            out.push_str(&seq);
+        }
+    }
+
+    out
+}
+
+/**
+ * `print!`, filtered by the display options. Prints nothing when quiet
+ */
+macro_rules! tprint {
+    ($($arg:tt)*) => {
+        if !$crate::util::term::quiet() {
+            print!("{}", $crate::util::term::filter(&format!($($arg)*)))
+        }
+    };
+}
+
+/**
+ * `println!`, filtered by the display options. Prints nothing when quiet
+ */
+macro_rules! tprintln {
+    () => {
+        if !$crate::util::term::quiet() {
+            println!()
+        }
+    };
+    ($($arg:tt)*) => {
+        if !$crate::util::term::quiet() {
+            println!("{}", $crate::util::term::filter(&format!($($arg)*)))
+        }
+    };
+}
+
+/**
+ * Print a prompt filtered by the display options, then read a line of input
+ */
+macro_rules! tinput {
+    ($($arg:tt)*) => {{
+        print!("{}", $crate::util::term::filter(&format!($($arg)*)));
+        $crate::util::input::read_line()
+    }};
+}
+
+pub(crate) use {tinput, tprint, tprintln};
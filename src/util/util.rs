@@ -6,7 +6,7 @@ use std::fmt::Display;
  */
 pub fn format_vec_string<T>(v: &[T]) -> String where T: Display {
     // if there are no elements, return empty
-    if v.len() == 0 {
+    if v.is_empty() {
         return String::from("");
     }
 